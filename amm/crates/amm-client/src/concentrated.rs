@@ -0,0 +1,144 @@
+use amm::{accounts, instruction as ix};
+use anchor_lang::{
+    prelude::{AccountMeta, Pubkey},
+    solana_program::{instruction::Instruction, system_program},
+};
+
+use crate::{
+    instruction, AmmProgram, DecreaseLiquidityArgs, IncreaseLiquidityArgs,
+    InitializeConcentratedArgs, OpenPositionArgs, Pool, SwapConcentratedArgs,
+};
+
+impl Pool {
+    pub fn initialize_concentrated(
+        &self,
+        authority: &Pubkey,
+        args: InitializeConcentratedArgs,
+    ) -> Instruction {
+        let accounts = accounts::InitConcentrated {
+            authority: *authority,
+            config: self.config,
+            pool: AmmProgram::concentrated_pool(&self.config),
+            bitmap: AmmProgram::bitmap(&self.config),
+            system_program: system_program::ID,
+        };
+
+        instruction(accounts, args)
+    }
+
+    pub fn open_position(&self, owner: &Pubkey, args: OpenPositionArgs) -> Instruction {
+        let accounts = accounts::OpenPosition {
+            owner: *owner,
+            config: self.config,
+            pool: AmmProgram::concentrated_pool(&self.config),
+            lower_tick: AmmProgram::tick(&self.config, args.tick_lower),
+            upper_tick: AmmProgram::tick(&self.config, args.tick_upper),
+            position: AmmProgram::position(&self.config, owner, args.tick_lower, args.tick_upper),
+            system_program: system_program::ID,
+        };
+
+        instruction(accounts, args)
+    }
+
+    /// Accounts of the position `owner` holds over `[tick_lower, tick_upper)`.
+    fn modify_position_accounts(
+        &self,
+        owner: &Pubkey,
+        tick_lower: i32,
+        tick_upper: i32,
+    ) -> accounts::ModifyPosition {
+        accounts::ModifyPosition {
+            owner: *owner,
+            config: self.config,
+            mint_x: self.mint_x,
+            mint_y: self.mint_y,
+            pool: AmmProgram::concentrated_pool(&self.config),
+            bitmap: AmmProgram::bitmap(&self.config),
+            position: AmmProgram::position(&self.config, owner, tick_lower, tick_upper),
+            lower_tick: AmmProgram::tick(&self.config, tick_lower),
+            upper_tick: AmmProgram::tick(&self.config, tick_upper),
+            user_x_ata: self.ata(owner, &self.mint_x),
+            user_y_ata: self.ata(owner, &self.mint_y),
+            vault_x: self.vault_x,
+            vault_y: self.vault_y,
+            token_program: self.token_program,
+        }
+    }
+
+    pub fn increase_liquidity(
+        &self,
+        owner: &Pubkey,
+        tick_lower: i32,
+        tick_upper: i32,
+        args: IncreaseLiquidityArgs,
+    ) -> Instruction {
+        instruction(
+            self.modify_position_accounts(owner, tick_lower, tick_upper),
+            args,
+        )
+    }
+
+    pub fn decrease_liquidity(
+        &self,
+        owner: &Pubkey,
+        tick_lower: i32,
+        tick_upper: i32,
+        args: DecreaseLiquidityArgs,
+    ) -> Instruction {
+        instruction(
+            self.modify_position_accounts(owner, tick_lower, tick_upper),
+            args,
+        )
+    }
+
+    pub fn collect_fees(&self, owner: &Pubkey, tick_lower: i32, tick_upper: i32) -> Instruction {
+        instruction(
+            self.modify_position_accounts(owner, tick_lower, tick_upper),
+            ix::CollectFees {},
+        )
+    }
+
+    pub fn close_position(&self, owner: &Pubkey, tick_lower: i32, tick_upper: i32) -> Instruction {
+        let accounts = accounts::ClosePosition {
+            owner: *owner,
+            config: self.config,
+            position: AmmProgram::position(&self.config, owner, tick_lower, tick_upper),
+        };
+
+        instruction(accounts, ix::ClosePosition {})
+    }
+
+    /// `swap_concentrated`, passing the initialized `ticks` the swap is
+    /// expected to cross in crossing order.
+    pub fn swap_concentrated(
+        &self,
+        user: &Pubkey,
+        ticks: &[i32],
+        args: SwapConcentratedArgs,
+    ) -> Instruction {
+        let accounts = accounts::SwapConcentrated {
+            user: *user,
+            config: self.config,
+            mint_x: self.mint_x,
+            mint_y: self.mint_y,
+            pool: AmmProgram::concentrated_pool(&self.config),
+            bitmap: AmmProgram::bitmap(&self.config),
+            user_x_ata: self.ata(user, &self.mint_x),
+            user_y_ata: self.ata(user, &self.mint_y),
+            vault_x: self.vault_x,
+            vault_y: self.vault_y,
+            fee_vault_x: AmmProgram::fee_vault_x(&self.config),
+            fee_vault_y: AmmProgram::fee_vault_y(&self.config),
+            token_program: self.token_program,
+        };
+
+        let mut ix = instruction(accounts, args);
+        ix.accounts.extend(
+            ticks
+                .iter()
+                .map(|index| AccountMeta::new(AmmProgram::tick(&self.config, *index), false)),
+        );
+
+        ix
+    }
+}
@@ -0,0 +1,88 @@
+use amm::{accounts, instruction as ix};
+use anchor_lang::{
+    prelude::Pubkey,
+    solana_program::{instruction::Instruction, system_program},
+};
+use anchor_spl::associated_token;
+
+use crate::{instruction, AmmProgram, FundRewardsArgs, Pool, StakeArgs, UnstakeArgs};
+
+impl Pool {
+    pub fn initialize_farm(&self, authority: &Pubkey, reward_mint: &Pubkey) -> Instruction {
+        let farm = AmmProgram::farm(&self.config);
+        let accounts = accounts::InitFarm {
+            authority: *authority,
+            config: self.config,
+            mint_lp: self.mint_lp(),
+            reward_mint: *reward_mint,
+            farm,
+            stake_vault: AmmProgram::farm_stake_vault(&farm),
+            reward_vault: AmmProgram::farm_reward_vault(&farm),
+            token_program: self.token_program,
+            system_program: system_program::ID,
+        };
+
+        instruction(accounts, ix::InitializeFarm {})
+    }
+
+    /// `fund_rewards` from `authority`'s ATA of `reward_mint`.
+    pub fn fund_rewards(
+        &self,
+        authority: &Pubkey,
+        reward_mint: &Pubkey,
+        args: FundRewardsArgs,
+    ) -> Instruction {
+        let farm = AmmProgram::farm(&self.config);
+        let accounts = accounts::FundRewards {
+            authority: *authority,
+            config: self.config,
+            farm,
+            reward_mint: *reward_mint,
+            authority_reward_ata: self.ata(authority, reward_mint),
+            reward_vault: AmmProgram::farm_reward_vault(&farm),
+            token_program: self.token_program,
+        };
+
+        instruction(accounts, args)
+    }
+
+    fn modify_stake_accounts(&self, owner: &Pubkey) -> accounts::ModifyStake {
+        let farm = AmmProgram::farm(&self.config);
+        accounts::ModifyStake {
+            owner: *owner,
+            config: self.config,
+            farm,
+            mint_lp: self.mint_lp(),
+            stake: AmmProgram::stake(&farm, owner),
+            user_lp_ata: self.ata(owner, &self.mint_lp()),
+            stake_vault: AmmProgram::farm_stake_vault(&farm),
+            token_program: self.token_program,
+            system_program: system_program::ID,
+        }
+    }
+
+    pub fn stake(&self, owner: &Pubkey, args: StakeArgs) -> Instruction {
+        instruction(self.modify_stake_accounts(owner), args)
+    }
+
+    pub fn unstake(&self, owner: &Pubkey, args: UnstakeArgs) -> Instruction {
+        instruction(self.modify_stake_accounts(owner), args)
+    }
+
+    pub fn claim(&self, owner: &Pubkey, reward_mint: &Pubkey) -> Instruction {
+        let farm = AmmProgram::farm(&self.config);
+        let accounts = accounts::ClaimRewards {
+            owner: *owner,
+            farm,
+            reward_mint: *reward_mint,
+            stake: AmmProgram::stake(&farm, owner),
+            reward_vault: AmmProgram::farm_reward_vault(&farm),
+            user_reward_ata: self.ata(owner, reward_mint),
+            associated_token_program: associated_token::ID,
+            token_program: self.token_program,
+            system_program: system_program::ID,
+        };
+
+        instruction(accounts, ix::Claim {})
+    }
+}
@@ -0,0 +1,178 @@
+//! Rust client for the AMM program.
+//!
+//! `AmmProgram` derives the program's PDAs the way its account constraints
+//! seed them, and `Pool` builds an `Instruction` for every AMM instruction
+//! with its accounts in program order. Account lists and instruction data
+//! come from the program's own generated client types, so they cannot drift
+//! from the on-chain definitions.
+
+use anchor_lang::{
+    prelude::Pubkey,
+    solana_program::{bpf_loader_upgradeable, instruction::Instruction},
+    AccountDeserialize, InstructionData, ToAccountMetas,
+};
+
+mod concentrated;
+mod farm;
+mod pool;
+
+pub use amm::{
+    instruction::{
+        DecreaseLiquidity as DecreaseLiquidityArgs, Deposit as DepositArgs,
+        DepositSingle as DepositSingleArgs, FlashLoan as FlashLoanArgs,
+        FundRewards as FundRewardsArgs, IncreaseLiquidity as IncreaseLiquidityArgs,
+        InitializeAmm as InitializeAmmArgs, InitializeConcentrated as InitializeConcentratedArgs,
+        Observe as ObserveArgs, OpenPosition as OpenPositionArgs,
+        ProposeAuthority as ProposeAuthorityArgs, RampAmp as RampAmpArgs,
+        SetDynamicFee as SetDynamicFeeArgs, Stake as StakeArgs, Swap as SwapArgs,
+        SwapConcentrated as SwapConcentratedArgs, SwapExactOut as SwapExactOutArgs,
+        SwapRoute as SwapRouteArgs, Unstake as UnstakeArgs, UpdateFee as UpdateFeeArgs,
+        UpdateFlashLoanFee as UpdateFlashLoanFeeArgs, UpdateMaxTrade as UpdateMaxTradeArgs,
+        Withdraw as WithdrawArgs, WithdrawSingle as WithdrawSingleArgs,
+    },
+    ConcentratedPool, Config, CurveType, Farm, Oracle, PairIndex, PoolEntry, Position, Registry,
+    Stake, Tick, Twap, ID,
+};
+pub use pool::*;
+
+pub struct AmmProgram;
+
+impl AmmProgram {
+    pub fn id() -> Pubkey {
+        ID
+    }
+
+    pub fn derive_program_address(seeds: &[&[u8]]) -> Pubkey {
+        Pubkey::find_program_address(seeds, &ID).0
+    }
+
+    /// Pool config, seeded by `Init` from the seed and both mints.
+    pub fn config(seed: u64, mint_x: &Pubkey, mint_y: &Pubkey) -> Pubkey {
+        Self::derive_program_address(&[
+            b"config",
+            seed.to_le_bytes().as_ref(),
+            mint_x.as_ref(),
+            mint_y.as_ref(),
+        ])
+    }
+
+    /// Program data account of the upgradeable program, which records its
+    /// upgrade authority.
+    pub fn program_data() -> Pubkey {
+        Pubkey::find_program_address(&[ID.as_ref()], &bpf_loader_upgradeable::ID).0
+    }
+
+    /// Program-wide pool counters.
+    pub fn registry() -> Pubkey {
+        Self::derive_program_address(&[b"registry"])
+    }
+
+    /// Index of every pool of a pair, whose mints may be given in either
+    /// order.
+    pub fn pair(mint_x: &Pubkey, mint_y: &Pubkey) -> Pubkey {
+        let (mint_x, mint_y) = match mint_x < mint_y {
+            true => (mint_x, mint_y),
+            false => (mint_y, mint_x),
+        };
+        Self::derive_program_address(&[b"pair", mint_x.as_ref(), mint_y.as_ref()])
+    }
+
+    pub fn lp_mint(config: &Pubkey) -> Pubkey {
+        Self::derive_program_address(&[b"lp", config.as_ref()])
+    }
+
+    /// LP mint authority of `config`.
+    pub fn auth(config: &Pubkey) -> Pubkey {
+        Self::derive_program_address(&[b"auth", config.as_ref()])
+    }
+
+    /// Program-wide LP mint authority of pools created before `migrate_auth`.
+    pub fn legacy_auth() -> Pubkey {
+        Self::derive_program_address(&[b"auth"])
+    }
+
+    /// Owner of the permanently locked first-deposit LP.
+    pub fn dead(config: &Pubkey) -> Pubkey {
+        Self::derive_program_address(&[b"dead", config.as_ref()])
+    }
+
+    pub fn lp_lock(config: &Pubkey) -> Pubkey {
+        Self::derive_program_address(&[b"lp_lock", config.as_ref()])
+    }
+
+    pub fn fee_vault_x(config: &Pubkey) -> Pubkey {
+        Self::derive_program_address(&[b"fee_x", config.as_ref()])
+    }
+
+    pub fn fee_vault_y(config: &Pubkey) -> Pubkey {
+        Self::derive_program_address(&[b"fee_y", config.as_ref()])
+    }
+
+    pub fn oracle(config: &Pubkey) -> Pubkey {
+        Self::derive_program_address(&[b"oracle", config.as_ref()])
+    }
+
+    pub fn concentrated_pool(config: &Pubkey) -> Pubkey {
+        Self::derive_program_address(&[b"concentrated", config.as_ref()])
+    }
+
+    pub fn bitmap(config: &Pubkey) -> Pubkey {
+        Self::derive_program_address(&[b"bitmap", config.as_ref()])
+    }
+
+    pub fn tick(config: &Pubkey, index: i32) -> Pubkey {
+        Self::derive_program_address(&[b"tick", config.as_ref(), index.to_le_bytes().as_ref()])
+    }
+
+    pub fn position(config: &Pubkey, owner: &Pubkey, tick_lower: i32, tick_upper: i32) -> Pubkey {
+        Self::derive_program_address(&[
+            b"position",
+            config.as_ref(),
+            owner.as_ref(),
+            tick_lower.to_le_bytes().as_ref(),
+            tick_upper.to_le_bytes().as_ref(),
+        ])
+    }
+
+    /// Liquidity mining farm of the pool at `config`.
+    pub fn farm(config: &Pubkey) -> Pubkey {
+        Self::derive_program_address(&[b"farm", config.as_ref()])
+    }
+
+    /// Vault holding the LP staked in `farm`.
+    pub fn farm_stake_vault(farm: &Pubkey) -> Pubkey {
+        Self::derive_program_address(&[b"farm_lp", farm.as_ref()])
+    }
+
+    /// Vault holding the rewards `farm` streams.
+    pub fn farm_reward_vault(farm: &Pubkey) -> Pubkey {
+        Self::derive_program_address(&[b"farm_rewards", farm.as_ref()])
+    }
+
+    pub fn stake(farm: &Pubkey, owner: &Pubkey) -> Pubkey {
+        Self::derive_program_address(&[b"stake", farm.as_ref(), owner.as_ref()])
+    }
+}
+
+/// Deserializes a pool `Config` from raw account data, discriminator included.
+pub fn deserialize_config(data: &[u8]) -> anchor_lang::Result<Config> {
+    Config::try_deserialize(&mut &data[..])
+}
+
+/// Deserializes a `PairIndex` from raw account data, discriminator included.
+pub fn deserialize_pair_index(data: &[u8]) -> anchor_lang::Result<PairIndex> {
+    PairIndex::try_deserialize(&mut &data[..])
+}
+
+/// Deserializes the `Registry` from raw account data, discriminator included.
+pub fn deserialize_registry(data: &[u8]) -> anchor_lang::Result<Registry> {
+    Registry::try_deserialize(&mut &data[..])
+}
+
+fn instruction(accounts: impl ToAccountMetas, args: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    }
+}
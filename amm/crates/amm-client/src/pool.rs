@@ -0,0 +1,459 @@
+use amm::{accounts, instruction as ix};
+use anchor_lang::{
+    prelude::{AccountMeta, Pubkey},
+    solana_program::{instruction::Instruction, system_program, sysvar},
+};
+use anchor_spl::associated_token::{self, get_associated_token_address_with_program_id};
+
+use crate::{
+    instruction, AmmProgram, Config, DepositArgs, DepositSingleArgs, FlashLoanArgs,
+    InitializeAmmArgs, ObserveArgs, ProposeAuthorityArgs, RampAmpArgs, SetDynamicFeeArgs, SwapArgs,
+    SwapExactOutArgs, SwapRouteArgs, UpdateFeeArgs, UpdateFlashLoanFeeArgs, UpdateMaxTradeArgs,
+    WithdrawArgs, WithdrawSingleArgs, ID,
+};
+
+/// Addresses of a single pool. The vaults are plain token accounts created
+/// from keypairs at initialization, so unlike the PDAs they have to be known
+/// up front or read from the pool's `Config`. User token accounts default to
+/// the user's ATAs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Pool {
+    pub config: Pubkey,
+    pub mint_x: Pubkey,
+    pub mint_y: Pubkey,
+    pub vault_x: Pubkey,
+    pub vault_y: Pubkey,
+    pub token_program: Pubkey,
+}
+
+impl Pool {
+    pub fn new(
+        seed: u64,
+        mint_x: Pubkey,
+        mint_y: Pubkey,
+        vault_x: Pubkey,
+        vault_y: Pubkey,
+        token_program: Pubkey,
+    ) -> Self {
+        Self {
+            config: AmmProgram::config(seed, &mint_x, &mint_y),
+            mint_x,
+            mint_y,
+            vault_x,
+            vault_y,
+            token_program,
+        }
+    }
+
+    /// Pool at `config` described by its deserialized account.
+    pub fn from_config(config: Pubkey, state: &Config, token_program: Pubkey) -> Self {
+        Self {
+            config,
+            mint_x: state.mint_x,
+            mint_y: state.mint_y,
+            vault_x: state.vault_x,
+            vault_y: state.vault_y,
+            token_program,
+        }
+    }
+
+    pub fn mint_lp(&self) -> Pubkey {
+        AmmProgram::lp_mint(&self.config)
+    }
+
+    /// `owner`'s associated token account for `mint`.
+    pub fn ata(&self, owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+        get_associated_token_address_with_program_id(owner, mint, &self.token_program)
+    }
+
+    /// Mint, vault and fee vault on the `is_x` side.
+    fn side(&self, is_x: bool) -> (Pubkey, Pubkey, Pubkey) {
+        match is_x {
+            true => (
+                self.mint_x,
+                self.vault_x,
+                AmmProgram::fee_vault_x(&self.config),
+            ),
+            false => (
+                self.mint_y,
+                self.vault_y,
+                AmmProgram::fee_vault_y(&self.config),
+            ),
+        }
+    }
+
+    /// `initialize_amm`. The vault keypairs must sign alongside `initializer`.
+    pub fn initialize_amm(&self, initializer: &Pubkey, args: InitializeAmmArgs) -> Instruction {
+        let accounts = accounts::Init {
+            initializer: *initializer,
+            config: self.config,
+            mint_lp: self.mint_lp(),
+            auth: AmmProgram::auth(&self.config),
+            dead: AmmProgram::dead(&self.config),
+            lp_lock: AmmProgram::lp_lock(&self.config),
+            mint_x: self.mint_x,
+            mint_y: self.mint_y,
+            vault_x: self.vault_x,
+            vault_y: self.vault_y,
+            fee_vault_x: AmmProgram::fee_vault_x(&self.config),
+            fee_vault_y: AmmProgram::fee_vault_y(&self.config),
+            oracle: AmmProgram::oracle(&self.config),
+            registry: AmmProgram::registry(),
+            pair: AmmProgram::pair(&self.mint_x, &self.mint_y),
+            associated_token_program: associated_token::ID,
+            token_program: self.token_program,
+            system_program: system_program::ID,
+        };
+
+        instruction(accounts, args)
+    }
+
+    pub fn deposit(&self, user: &Pubkey, args: DepositArgs) -> Instruction {
+        let accounts = accounts::Deposit {
+            user: *user,
+            config: self.config,
+            mint_lp: self.mint_lp(),
+            mint_x: self.mint_x,
+            mint_y: self.mint_y,
+            user_x_ata: self.ata(user, &self.mint_x),
+            user_y_ata: self.ata(user, &self.mint_y),
+            user_lp_ata: self.ata(user, &self.mint_lp()),
+            vault_x: self.vault_x,
+            vault_y: self.vault_y,
+            lp_lock: AmmProgram::lp_lock(&self.config),
+            auth: AmmProgram::auth(&self.config),
+            oracle: AmmProgram::oracle(&self.config),
+            associated_token_program: associated_token::ID,
+            token_program: self.token_program,
+            system_program: system_program::ID,
+        };
+
+        instruction(accounts, args)
+    }
+
+    pub fn deposit_single(&self, user: &Pubkey, args: DepositSingleArgs) -> Instruction {
+        let accounts = accounts::DepositSingle {
+            user: *user,
+            config: self.config,
+            mint_lp: self.mint_lp(),
+            mint_x: self.mint_x,
+            mint_y: self.mint_y,
+            user_x_ata: self.ata(user, &self.mint_x),
+            user_y_ata: self.ata(user, &self.mint_y),
+            user_lp_ata: self.ata(user, &self.mint_lp()),
+            vault_x: self.vault_x,
+            vault_y: self.vault_y,
+            fee_vault_x: AmmProgram::fee_vault_x(&self.config),
+            fee_vault_y: AmmProgram::fee_vault_y(&self.config),
+            auth: AmmProgram::auth(&self.config),
+            oracle: AmmProgram::oracle(&self.config),
+            associated_token_program: associated_token::ID,
+            token_program: self.token_program,
+            system_program: system_program::ID,
+        };
+
+        instruction(accounts, args)
+    }
+
+    pub fn withdraw(&self, user: &Pubkey, args: WithdrawArgs) -> Instruction {
+        let accounts = accounts::Withdaw {
+            user: *user,
+            config: self.config,
+            mint_lp: self.mint_lp(),
+            mint_x: self.mint_x,
+            mint_y: self.mint_y,
+            user_x_ata: self.ata(user, &self.mint_x),
+            user_y_ata: self.ata(user, &self.mint_y),
+            user_lp_ata: self.ata(user, &self.mint_lp()),
+            vault_x: self.vault_x,
+            vault_y: self.vault_y,
+            oracle: AmmProgram::oracle(&self.config),
+            associated_token_program: associated_token::ID,
+            token_program: self.token_program,
+            system_program: system_program::ID,
+        };
+
+        instruction(accounts, args)
+    }
+
+    pub fn withdraw_single(&self, user: &Pubkey, args: WithdrawSingleArgs) -> Instruction {
+        let accounts = accounts::WithdrawSingle {
+            user: *user,
+            config: self.config,
+            mint_lp: self.mint_lp(),
+            mint_x: self.mint_x,
+            mint_y: self.mint_y,
+            user_x_ata: self.ata(user, &self.mint_x),
+            user_y_ata: self.ata(user, &self.mint_y),
+            user_lp_ata: self.ata(user, &self.mint_lp()),
+            vault_x: self.vault_x,
+            vault_y: self.vault_y,
+            fee_vault_x: AmmProgram::fee_vault_x(&self.config),
+            fee_vault_y: AmmProgram::fee_vault_y(&self.config),
+            oracle: AmmProgram::oracle(&self.config),
+            associated_token_program: associated_token::ID,
+            token_program: self.token_program,
+            system_program: system_program::ID,
+        };
+
+        instruction(accounts, args)
+    }
+
+    fn swap_accounts(&self, user: &Pubkey) -> accounts::Swap {
+        accounts::Swap {
+            user: *user,
+            config: self.config,
+            mint_lp: self.mint_lp(),
+            mint_x: self.mint_x,
+            mint_y: self.mint_y,
+            user_x_ata: self.ata(user, &self.mint_x),
+            user_y_ata: self.ata(user, &self.mint_y),
+            vault_x: self.vault_x,
+            vault_y: self.vault_y,
+            fee_vault_x: AmmProgram::fee_vault_x(&self.config),
+            fee_vault_y: AmmProgram::fee_vault_y(&self.config),
+            oracle: AmmProgram::oracle(&self.config),
+            associated_token_program: associated_token::ID,
+            token_program: self.token_program,
+            system_program: system_program::ID,
+        }
+    }
+
+    pub fn swap(&self, user: &Pubkey, args: SwapArgs) -> Instruction {
+        instruction(self.swap_accounts(user), args)
+    }
+
+    pub fn swap_exact_out(&self, user: &Pubkey, args: SwapExactOutArgs) -> Instruction {
+        instruction(self.swap_accounts(user), args)
+    }
+
+    /// `swap_route` through `hops`, each a pool and the direction to trade it
+    /// in (`true` for X to Y). Consecutive hops must share the token between
+    /// them.
+    pub fn swap_route(user: &Pubkey, hops: &[(Pool, bool)], args: SwapRouteArgs) -> Instruction {
+        let (first, first_is_x) = hops[0];
+        let (last, last_is_x) = hops[hops.len() - 1];
+        let (mint_in, _, _) = first.side(first_is_x);
+        let (mint_out, _, _) = last.side(!last_is_x);
+
+        let accounts = accounts::SwapRoute {
+            user: *user,
+            user_in_ata: first.ata(user, &mint_in),
+            user_out_ata: last.ata(user, &mint_out),
+            token_program: first.token_program,
+        };
+
+        let mut ix = instruction(accounts, args);
+        for (pool, is_x) in hops {
+            let (mint_in, vault_in, fee_vault_in) = pool.side(*is_x);
+            let (mint_out, vault_out, _) = pool.side(!is_x);
+
+            ix.accounts.extend([
+                AccountMeta::new(pool.config, false),
+                AccountMeta::new_readonly(mint_in, false),
+                AccountMeta::new_readonly(mint_out, false),
+                AccountMeta::new(vault_in, false),
+                AccountMeta::new(vault_out, false),
+                AccountMeta::new(fee_vault_in, false),
+                AccountMeta::new(AmmProgram::oracle(&pool.config), false),
+            ]);
+        }
+
+        ix
+    }
+
+    /// Migrates a legacy pool to the current layout. `admin` must be the
+    /// program's upgrade authority and becomes the pool authority.
+    pub fn migrate_auth(&self, admin: &Pubkey) -> Instruction {
+        let accounts = accounts::MigrateAuth {
+            admin: *admin,
+            program: ID,
+            program_data: AmmProgram::program_data(),
+            config: self.config,
+            mint_lp: self.mint_lp(),
+            mint_x: self.mint_x,
+            mint_y: self.mint_y,
+            legacy_auth: AmmProgram::legacy_auth(),
+            auth: AmmProgram::auth(&self.config),
+            dead: AmmProgram::dead(&self.config),
+            lp_lock: AmmProgram::lp_lock(&self.config),
+            vault_x: self.vault_x,
+            vault_y: self.vault_y,
+            fee_vault_x: AmmProgram::fee_vault_x(&self.config),
+            fee_vault_y: AmmProgram::fee_vault_y(&self.config),
+            oracle: AmmProgram::oracle(&self.config),
+            registry: AmmProgram::registry(),
+            pair: AmmProgram::pair(&self.mint_x, &self.mint_y),
+            token_program: self.token_program,
+            system_program: system_program::ID,
+        };
+
+        instruction(accounts, ix::MigrateAuth {})
+    }
+
+    /// `observe`; the TWAP is returned through the transaction's return data.
+    pub fn observe(&self, args: ObserveArgs) -> Instruction {
+        let accounts = accounts::Observe {
+            config: self.config,
+            vault_x: self.vault_x,
+            vault_y: self.vault_y,
+            oracle: AmmProgram::oracle(&self.config),
+        };
+
+        instruction(accounts, args)
+    }
+
+    pub fn collect_protocol_fees(
+        &self,
+        authority: &Pubkey,
+        treasury_x: &Pubkey,
+        treasury_y: &Pubkey,
+    ) -> Instruction {
+        let accounts = accounts::CollectProtocolFees {
+            authority: *authority,
+            config: self.config,
+            mint_x: self.mint_x,
+            mint_y: self.mint_y,
+            fee_vault_x: AmmProgram::fee_vault_x(&self.config),
+            fee_vault_y: AmmProgram::fee_vault_y(&self.config),
+            treasury_x: *treasury_x,
+            treasury_y: *treasury_y,
+            token_program: self.token_program,
+        };
+
+        instruction(accounts, ix::CollectProtocolFees {})
+    }
+
+    /// `freeze_pool`, locking the pool for good and delisting it so holders
+    /// can withdraw ahead of `close_pool`.
+    pub fn freeze_pool(&self, authority: &Pubkey) -> Instruction {
+        let accounts = accounts::FreezePool {
+            authority: *authority,
+            config: self.config,
+            pair: AmmProgram::pair(&self.mint_x, &self.mint_y),
+            registry: AmmProgram::registry(),
+        };
+
+        instruction(accounts, ix::FreezePool {})
+    }
+
+    /// `close_pool`, paying the remaining reserves out pro rata to every LP
+    /// `holder` through their ATAs. `holders` must cover all outstanding LP.
+    pub fn close_pool(
+        &self,
+        authority: &Pubkey,
+        treasury_x: &Pubkey,
+        treasury_y: &Pubkey,
+        holders: &[Pubkey],
+    ) -> Instruction {
+        let accounts = accounts::ClosePool {
+            authority: *authority,
+            config: self.config,
+            mint_lp: self.mint_lp(),
+            mint_x: self.mint_x,
+            mint_y: self.mint_y,
+            dead: AmmProgram::dead(&self.config),
+            lp_lock: AmmProgram::lp_lock(&self.config),
+            vault_x: self.vault_x,
+            vault_y: self.vault_y,
+            fee_vault_x: AmmProgram::fee_vault_x(&self.config),
+            fee_vault_y: AmmProgram::fee_vault_y(&self.config),
+            treasury_x: *treasury_x,
+            treasury_y: *treasury_y,
+            oracle: AmmProgram::oracle(&self.config),
+            farm: AmmProgram::farm(&self.config),
+            token_program: self.token_program,
+        };
+
+        let mut ix = instruction(accounts, ix::ClosePool {});
+        for holder in holders {
+            ix.accounts.extend([
+                AccountMeta::new_readonly(self.ata(holder, &self.mint_lp()), false),
+                AccountMeta::new(self.ata(holder, &self.mint_x), false),
+                AccountMeta::new(self.ata(holder, &self.mint_y), false),
+            ]);
+        }
+
+        ix
+    }
+
+    fn admin_accounts(&self, authority: &Pubkey) -> accounts::Admin {
+        accounts::Admin {
+            authority: *authority,
+            config: self.config,
+        }
+    }
+
+    pub fn lock(&self, authority: &Pubkey) -> Instruction {
+        instruction(self.admin_accounts(authority), ix::Lock {})
+    }
+
+    pub fn unlock(&self, authority: &Pubkey) -> Instruction {
+        instruction(self.admin_accounts(authority), ix::Unlock {})
+    }
+
+    pub fn update_fee(&self, authority: &Pubkey, args: UpdateFeeArgs) -> Instruction {
+        instruction(self.admin_accounts(authority), args)
+    }
+
+    pub fn ramp_amp(&self, authority: &Pubkey, args: RampAmpArgs) -> Instruction {
+        instruction(self.admin_accounts(authority), args)
+    }
+
+    pub fn stop_ramp_amp(&self, authority: &Pubkey) -> Instruction {
+        instruction(self.admin_accounts(authority), ix::StopRampAmp {})
+    }
+
+    pub fn set_dynamic_fee(&self, authority: &Pubkey, args: SetDynamicFeeArgs) -> Instruction {
+        instruction(self.admin_accounts(authority), args)
+    }
+
+    pub fn update_max_trade(&self, authority: &Pubkey, args: UpdateMaxTradeArgs) -> Instruction {
+        instruction(self.admin_accounts(authority), args)
+    }
+
+    pub fn update_flash_loan_fee(
+        &self,
+        authority: &Pubkey,
+        args: UpdateFlashLoanFeeArgs,
+    ) -> Instruction {
+        instruction(self.admin_accounts(authority), args)
+    }
+
+    pub fn propose_authority(&self, authority: &Pubkey, args: ProposeAuthorityArgs) -> Instruction {
+        instruction(self.admin_accounts(authority), args)
+    }
+
+    pub fn accept_authority(&self, pending_authority: &Pubkey) -> Instruction {
+        let accounts = accounts::AcceptAuthority {
+            pending_authority: *pending_authority,
+            config: self.config,
+        };
+
+        instruction(accounts, ix::AcceptAuthority {})
+    }
+
+    fn flash_loan_accounts(&self, user: &Pubkey) -> accounts::FlashLoan {
+        accounts::FlashLoan {
+            user: *user,
+            config: self.config,
+            mint_x: self.mint_x,
+            mint_y: self.mint_y,
+            user_x_ata: self.ata(user, &self.mint_x),
+            user_y_ata: self.ata(user, &self.mint_y),
+            vault_x: self.vault_x,
+            vault_y: self.vault_y,
+            instructions: sysvar::instructions::ID,
+            token_program: self.token_program,
+        }
+    }
+
+    /// `flash_loan`. The same transaction must end with `flash_repay`.
+    pub fn flash_loan(&self, user: &Pubkey, args: FlashLoanArgs) -> Instruction {
+        instruction(self.flash_loan_accounts(user), args)
+    }
+
+    pub fn flash_repay(&self, user: &Pubkey) -> Instruction {
+        instruction(self.flash_loan_accounts(user), ix::FlashRepay {})
+    }
+}
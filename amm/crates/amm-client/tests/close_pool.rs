@@ -0,0 +1,240 @@
+//! Closing a pool freezes it first, lets holders withdraw, then pays every
+//! remaining LP holder their share of the reserves once its farm is empty.
+
+mod common;
+
+use amm::AmmError;
+use amm_client::{DepositArgs, Pool, StakeArgs, SwapArgs, UnstakeArgs, WithdrawArgs};
+use amm_math::quote_withdraw;
+use common::{is_amm_error, PoolOptions, TestBank};
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+const BALANCE: u64 = 1_000_000_000;
+const SEED_X: u64 = 100_000_000;
+const SEED_Y: u64 = 300_000_000;
+
+/// A constant-product pool seeded by `provider`, with a tenth of its LP
+/// held by `user`. The payer is the pool authority and holds the treasury.
+async fn pool_with_holders() -> (TestBank, Pool, Keypair, Keypair) {
+    let mut bank = TestBank::new().await;
+    let pool = bank
+        .create_pool(PoolOptions {
+            fee: 30,
+            ..Default::default()
+        })
+        .await;
+
+    let provider = Keypair::new();
+    let user = Keypair::new();
+    bank.fund_pool(&pool, &[&provider, &user], BALANCE).await;
+    let payer = bank.payer();
+    bank.fund(&payer, &[pool.mint_x, pool.mint_y], 0, &pool.token_program)
+        .await;
+    bank.seed_pool(&pool, &provider, SEED_X, SEED_Y).await;
+
+    let (_, _, supply) = bank.reserves(&pool).await;
+    let ix = pool.deposit(
+        &user.pubkey(),
+        DepositArgs {
+            amount: supply / 10,
+            max_x: BALANCE,
+            max_y: BALANCE,
+            expiration: i64::MAX,
+            max_slot: None,
+        },
+    );
+    bank.send(&[ix], &[&user]).await.unwrap();
+
+    (bank, pool, provider, user)
+}
+
+fn close(bank: &TestBank, pool: &Pool, holders: &[Pubkey]) -> Instruction {
+    let authority = bank.payer();
+    pool.close_pool(
+        &authority,
+        &pool.ata(&authority, &pool.mint_x),
+        &pool.ata(&authority, &pool.mint_y),
+        holders,
+    )
+}
+
+async fn freeze(bank: &mut TestBank, pool: &Pool) {
+    let ix = pool.freeze_pool(&bank.payer());
+    bank.send(&[ix], &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn holders_withdraw_after_the_pool_is_frozen() {
+    let (mut bank, pool, provider, user) = pool_with_holders().await;
+    let authority = bank.payer();
+
+    let result = bank.send(&[close(&bank, &pool, &[])], &[]).await;
+    assert!(is_amm_error(&result, AmmError::PoolNotFrozen));
+
+    let registry = bank.registry().await;
+    freeze(&mut bank, &pool).await;
+
+    let after = bank.registry().await;
+    assert_eq!(after.pools, registry.pools - 1);
+    assert_eq!(after.pairs, registry.pairs - 1);
+
+    let ix = pool.freeze_pool(&authority);
+    let result = bank.send(&[ix], &[]).await;
+    assert!(is_amm_error(&result, AmmError::PoolFrozen));
+
+    let ix = pool.unlock(&authority);
+    let result = bank.send(&[ix], &[]).await;
+    assert!(is_amm_error(&result, AmmError::PoolFrozen));
+
+    let ix = pool.swap(
+        &user.pubkey(),
+        SwapArgs {
+            amount_in: 1_000,
+            min_out: 1,
+            expiration: i64::MAX,
+            max_slot: None,
+            is_x: true,
+        },
+    );
+    let result = bank.send(&[ix], &[&user]).await;
+    assert!(is_amm_error(&result, AmmError::Locked));
+
+    // The user leaves on their own; only the provider is left to settle.
+    let lp = bank
+        .balance(&pool.ata(&user.pubkey(), &pool.mint_lp()))
+        .await;
+    let ix = pool.withdraw(
+        &user.pubkey(),
+        WithdrawArgs {
+            amount: lp,
+            min_x: 1,
+            min_y: 1,
+            expiration: i64::MAX,
+            max_slot: None,
+        },
+    );
+    bank.send(&[ix], &[&user]).await.unwrap();
+
+    let (x, y, supply) = bank.reserves(&pool).await;
+    let holder = provider.pubkey();
+    let lp = bank.balance(&pool.ata(&holder, &pool.mint_lp())).await;
+    let (dx, dy) = quote_withdraw(x, y, supply, lp).unwrap();
+    let balance_x = bank.balance(&pool.ata(&holder, &pool.mint_x)).await;
+    let balance_y = bank.balance(&pool.ata(&holder, &pool.mint_y)).await;
+
+    bank.send(&[close(&bank, &pool, &[holder])], &[])
+        .await
+        .unwrap();
+
+    assert_eq!(
+        bank.balance(&pool.ata(&holder, &pool.mint_x)).await,
+        balance_x + dx
+    );
+    assert_eq!(
+        bank.balance(&pool.ata(&holder, &pool.mint_y)).await,
+        balance_y + dy
+    );
+
+    let account = bank
+        .context
+        .banks_client
+        .get_account(pool.config)
+        .await
+        .unwrap();
+    assert!(account.is_none());
+}
+
+#[tokio::test]
+async fn rejects_a_missing_holder() {
+    let (mut bank, pool, provider, _user) = pool_with_holders().await;
+    freeze(&mut bank, &pool).await;
+
+    let ix = close(&bank, &pool, &[provider.pubkey()]);
+    let result = bank.send(&[ix], &[]).await;
+    assert!(is_amm_error(&result, AmmError::PoolNotEmpty));
+}
+
+#[tokio::test]
+async fn rejects_a_duplicate_holder() {
+    let (mut bank, pool, provider, user) = pool_with_holders().await;
+    freeze(&mut bank, &pool).await;
+
+    let holders = [provider.pubkey(), user.pubkey(), user.pubkey()];
+    let result = bank.send(&[close(&bank, &pool, &holders)], &[]).await;
+    assert!(is_amm_error(&result, AmmError::HolderMismatch));
+}
+
+#[tokio::test]
+async fn rejects_payouts_to_another_owner() {
+    let (mut bank, pool, provider, user) = pool_with_holders().await;
+    freeze(&mut bank, &pool).await;
+
+    let holders = [provider.pubkey(), user.pubkey()];
+    for mint in [pool.mint_x, pool.mint_y] {
+        // Redirect the user's share to the provider's account.
+        let mut ix = close(&bank, &pool, &holders);
+        let to = pool.ata(&user.pubkey(), &mint);
+        let meta = ix
+            .accounts
+            .iter_mut()
+            .find(|meta| meta.pubkey == to)
+            .unwrap();
+        meta.pubkey = pool.ata(&provider.pubkey(), &mint);
+
+        let result = bank.send(&[ix], &[]).await;
+        assert!(is_amm_error(&result, AmmError::HolderMismatch));
+    }
+}
+
+#[tokio::test]
+async fn pays_out_holders_once_the_farm_is_empty() {
+    let (mut bank, pool, provider, user) = pool_with_holders().await;
+    let authority = bank.payer();
+    let holders = [provider.pubkey(), user.pubkey()];
+
+    // Staked LP would be stranded in the farm once the config is gone.
+    let staked = bank
+        .balance(&pool.ata(&user.pubkey(), &pool.mint_lp()))
+        .await;
+    let ix = pool.initialize_farm(&authority, &pool.mint_x);
+    bank.send(&[ix], &[]).await.unwrap();
+    let ix = pool.stake(&user.pubkey(), StakeArgs { amount: staked / 2 });
+    bank.send(&[ix], &[&user]).await.unwrap();
+
+    freeze(&mut bank, &pool).await;
+
+    let ix = pool.stake(&user.pubkey(), StakeArgs { amount: 1 });
+    let result = bank.send(&[ix], &[&user]).await;
+    assert!(is_amm_error(&result, AmmError::PoolFrozen));
+
+    let result = bank.send(&[close(&bank, &pool, &holders)], &[]).await;
+    assert!(is_amm_error(&result, AmmError::FarmNotEmpty));
+
+    let ix = pool.unstake(&user.pubkey(), UnstakeArgs { amount: staked / 2 });
+    bank.send(&[ix], &[&user]).await.unwrap();
+
+    let (x, y, supply) = bank.reserves(&pool).await;
+    let mut expected = Vec::new();
+    for holder in &holders {
+        let lp = bank.balance(&pool.ata(holder, &pool.mint_lp())).await;
+        let (dx, dy) = quote_withdraw(x, y, supply, lp).unwrap();
+        let balance_x = bank.balance(&pool.ata(holder, &pool.mint_x)).await;
+        let balance_y = bank.balance(&pool.ata(holder, &pool.mint_y)).await;
+        expected.push((balance_x + dx, balance_y + dy));
+    }
+
+    bank.send(&[close(&bank, &pool, &holders)], &[])
+        .await
+        .unwrap();
+
+    for (holder, (balance_x, balance_y)) in holders.iter().zip(expected) {
+        assert_eq!(
+            bank.balance(&pool.ata(holder, &pool.mint_x)).await,
+            balance_x
+        );
+        assert_eq!(
+            bank.balance(&pool.ata(holder, &pool.mint_y)).await,
+            balance_y
+        );
+    }
+}
@@ -0,0 +1,409 @@
+//! Setup shared by the program tests: an in-process bank running the compiled
+//! program from `target/deploy`, with helpers for mints, token accounts and
+//! pools. Build the program with `anchor build` first.
+
+#![allow(dead_code)]
+
+use amm::AmmError;
+use amm_client::{
+    deserialize_config, deserialize_pair_index, deserialize_registry, AmmProgram, Config,
+    CurveType, DepositArgs, InitializeAmmArgs, PairIndex, Pool, Registry,
+};
+use solana_program_test::{ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    clock::Clock, compute_budget::ComputeBudgetInstruction, instruction::Instruction,
+    instruction::InstructionError, pubkey::Pubkey, signature::Keypair, signer::Signer,
+    system_instruction, transaction::Transaction, transaction::TransactionError,
+};
+use spl_associated_token_account::{
+    get_associated_token_address_with_program_id,
+    instruction::create_associated_token_account_idempotent,
+};
+use spl_token_2022::extension::{
+    transfer_fee::instruction::initialize_transfer_fee_config, BaseStateWithExtensions,
+    ExtensionType, StateWithExtensions,
+};
+
+pub const DECIMALS: u8 = 6;
+
+/// Largest transfer fee charged by test mints with a transfer fee.
+pub const MAX_TRANSFER_FEE: u64 = u64::MAX;
+
+/// Pool built by `TestBank::create_pool`. The default is a fee-free
+/// constant-product pool on SPL Token mints.
+#[derive(Clone, Copy)]
+pub struct PoolOptions {
+    pub curve: CurveType,
+    /// StableSwap amplification coefficient.
+    pub amp: u64,
+    pub fee: u16,
+    pub protocol_fee: u16,
+    pub token_program: Pubkey,
+    /// Token-2022 transfer fee charged by both mints.
+    pub transfer_fee_bps: Option<u16>,
+}
+
+impl Default for PoolOptions {
+    fn default() -> Self {
+        Self {
+            curve: CurveType::ConstantProduct,
+            amp: 0,
+            fee: 0,
+            protocol_fee: 0,
+            token_program: spl_token::id(),
+            transfer_fee_bps: None,
+        }
+    }
+}
+
+impl PoolOptions {
+    fn args(&self) -> InitializeAmmArgs {
+        InitializeAmmArgs {
+            seed: 0,
+            fee: self.fee,
+            protocol_fee: self.protocol_fee,
+            curve: self.curve,
+            amp: self.amp,
+            flash_loan_fee: 0,
+        }
+    }
+}
+
+pub struct TestBank {
+    pub context: ProgramTestContext,
+    nonce: u32,
+}
+
+impl TestBank {
+    /// Program test loading the compiled AMM, ready for extra accounts.
+    pub fn program_test() -> ProgramTest {
+        std::env::set_var(
+            "SBF_OUT_DIR",
+            concat!(env!("CARGO_MANIFEST_DIR"), "/../../target/deploy"),
+        );
+        let mut program_test = ProgramTest::new("amm", AmmProgram::id(), None);
+        program_test.prefer_bpf(true);
+        program_test
+    }
+
+    pub async fn new() -> Self {
+        Self::start(Self::program_test()).await
+    }
+
+    pub async fn start(program_test: ProgramTest) -> Self {
+        Self {
+            context: program_test.start_with_context().await,
+            nonce: 0,
+        }
+    }
+
+    pub fn payer(&self) -> Pubkey {
+        self.context.payer.pubkey()
+    }
+
+    /// Sends `ixs` paid for by the payer.
+    pub async fn send(
+        &mut self,
+        ixs: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<(), TransactionError> {
+        // A distinct compute limit keeps repeated identical instructions from
+        // being rejected as already processed.
+        self.nonce += 1;
+        let mut instructions = vec![ComputeBudgetInstruction::set_compute_unit_limit(
+            1_000_000 + self.nonce,
+        )];
+        instructions.extend_from_slice(ixs);
+
+        let mut all_signers = vec![&self.context.payer];
+        all_signers.extend_from_slice(signers);
+
+        let transaction = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&self.context.payer.pubkey()),
+            &all_signers,
+            self.context.last_blockhash,
+        );
+
+        self.context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .map_err(|err| err.unwrap())
+    }
+
+    /// Creates a mint under `token_program` with the payer as its authority.
+    /// `transfer_fee_bps` adds a Token-2022 transfer fee.
+    pub async fn create_mint(
+        &mut self,
+        mint: &Keypair,
+        token_program: &Pubkey,
+        transfer_fee_bps: Option<u16>,
+    ) {
+        let payer = self.payer();
+        let extensions = match transfer_fee_bps {
+            Some(_) => vec![ExtensionType::TransferFeeConfig],
+            None => vec![],
+        };
+        let space =
+            ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&extensions)
+                .unwrap();
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+
+        let mut ixs = vec![system_instruction::create_account(
+            &payer,
+            &mint.pubkey(),
+            rent.minimum_balance(space),
+            space as u64,
+            token_program,
+        )];
+        if let Some(bps) = transfer_fee_bps {
+            ixs.push(
+                initialize_transfer_fee_config(
+                    token_program,
+                    &mint.pubkey(),
+                    Some(&payer),
+                    Some(&payer),
+                    bps,
+                    MAX_TRANSFER_FEE,
+                )
+                .unwrap(),
+            );
+        }
+        ixs.push(
+            spl_token_2022::instruction::initialize_mint2(
+                token_program,
+                &mint.pubkey(),
+                &payer,
+                None,
+                DECIMALS,
+            )
+            .unwrap(),
+        );
+
+        self.send(&ixs, &[mint]).await.unwrap();
+    }
+
+    /// Creates two mints, sorted as pools require, and a pool on them.
+    pub async fn create_pool(&mut self, options: PoolOptions) -> Pool {
+        let mut mints = [Keypair::new(), Keypair::new()];
+        mints.sort_by_key(|mint| mint.pubkey());
+        for mint in &mints {
+            self.create_mint(mint, &options.token_program, options.transfer_fee_bps)
+                .await;
+        }
+
+        self.create_pool_on(options, &mints[0].pubkey(), &mints[1].pubkey())
+            .await
+    }
+
+    /// Initializes a pool on existing mints, created under
+    /// `options.token_program`.
+    pub async fn create_pool_on(
+        &mut self,
+        options: PoolOptions,
+        mint_x: &Pubkey,
+        mint_y: &Pubkey,
+    ) -> Pool {
+        let args = options.args();
+        let vault_x = Keypair::new();
+        let vault_y = Keypair::new();
+        let pool = Pool::new(
+            args.seed,
+            *mint_x,
+            *mint_y,
+            vault_x.pubkey(),
+            vault_y.pubkey(),
+            options.token_program,
+        );
+
+        let ix = pool.initialize_amm(&self.payer(), args);
+        self.send(&[ix], &[&vault_x, &vault_y]).await.unwrap();
+
+        pool
+    }
+
+    /// Gives each of `owners` lamports and `amount` of both of `pool`'s
+    /// tokens.
+    pub async fn fund_pool(&mut self, pool: &Pool, owners: &[&Keypair], amount: u64) {
+        for owner in owners {
+            self.fund(
+                &owner.pubkey(),
+                &[pool.mint_x, pool.mint_y],
+                amount,
+                &pool.token_program,
+            )
+            .await;
+        }
+    }
+
+    /// First deposit into `pool`, by `provider`, of `max_x` and `max_y`.
+    pub async fn seed_pool(&mut self, pool: &Pool, provider: &Keypair, max_x: u64, max_y: u64) {
+        let ix = pool.deposit(
+            &provider.pubkey(),
+            DepositArgs {
+                amount: 1,
+                max_x,
+                max_y,
+                expiration: i64::MAX,
+                max_slot: None,
+            },
+        );
+        self.send(&[ix], &[provider]).await.unwrap();
+    }
+
+    /// Gives `user` lamports and `amount` of each of `mints`, creating their
+    /// token accounts as needed.
+    pub async fn fund(
+        &mut self,
+        user: &Pubkey,
+        mints: &[Pubkey],
+        amount: u64,
+        token_program: &Pubkey,
+    ) {
+        let payer = self.payer();
+
+        let mut ixs = vec![system_instruction::transfer(&payer, user, 1_000_000_000)];
+        for mint in mints {
+            ixs.push(create_associated_token_account_idempotent(
+                &payer,
+                user,
+                mint,
+                token_program,
+            ));
+            ixs.push(
+                spl_token_2022::instruction::mint_to(
+                    token_program,
+                    mint,
+                    &get_associated_token_address_with_program_id(user, mint, token_program),
+                    &payer,
+                    &[],
+                    amount,
+                )
+                .unwrap(),
+            );
+        }
+
+        self.send(&ixs, &[]).await.unwrap();
+    }
+
+    /// Token balance of `address`, zero if it does not exist.
+    pub async fn balance(&mut self, address: &Pubkey) -> u64 {
+        match self
+            .context
+            .banks_client
+            .get_account(*address)
+            .await
+            .unwrap()
+        {
+            Some(account) => {
+                StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account.data)
+                    .unwrap()
+                    .base
+                    .amount
+            }
+            None => 0,
+        }
+    }
+
+    /// Supply of `mint`.
+    pub async fn supply(&mut self, mint: &Pubkey) -> u64 {
+        let account = self
+            .context
+            .banks_client
+            .get_account(*mint)
+            .await
+            .unwrap()
+            .unwrap();
+
+        StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&account.data)
+            .unwrap()
+            .base
+            .supply
+    }
+
+    /// Transfer fee withheld on `amount` of `mint` in the current epoch.
+    pub async fn transfer_fee(&mut self, mint: &Pubkey, amount: u64) -> u64 {
+        let account = self
+            .context
+            .banks_client
+            .get_account(*mint)
+            .await
+            .unwrap()
+            .unwrap();
+        let state =
+            StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&account.data).unwrap();
+        let epoch = self.clock().await.epoch;
+
+        match state.get_extension::<spl_token_2022::extension::transfer_fee::TransferFeeConfig>() {
+            Ok(config) => config.calculate_epoch_fee(epoch, amount).unwrap(),
+            Err(_) => 0,
+        }
+    }
+
+    /// `(reserve_x, reserve_y, lp_supply)` of `pool`.
+    pub async fn reserves(&mut self, pool: &Pool) -> (u64, u64, u64) {
+        (
+            self.balance(&pool.vault_x).await,
+            self.balance(&pool.vault_y).await,
+            self.supply(&pool.mint_lp()).await,
+        )
+    }
+
+    pub async fn config(&mut self, pool: &Pool) -> Config {
+        let account = self
+            .context
+            .banks_client
+            .get_account(pool.config)
+            .await
+            .unwrap()
+            .unwrap();
+
+        deserialize_config(&account.data).unwrap()
+    }
+
+    pub async fn registry(&mut self) -> Registry {
+        let account = self
+            .context
+            .banks_client
+            .get_account(AmmProgram::registry())
+            .await
+            .unwrap()
+            .unwrap();
+
+        deserialize_registry(&account.data).unwrap()
+    }
+
+    pub async fn pair_index(&mut self, mint_x: &Pubkey, mint_y: &Pubkey) -> PairIndex {
+        let account = self
+            .context
+            .banks_client
+            .get_account(AmmProgram::pair(mint_x, mint_y))
+            .await
+            .unwrap()
+            .unwrap();
+
+        deserialize_pair_index(&account.data).unwrap()
+    }
+
+    pub async fn clock(&mut self) -> Clock {
+        self.context.banks_client.get_sysvar().await.unwrap()
+    }
+
+    /// Moves the bank clock to `unix_timestamp`.
+    pub async fn warp_to_timestamp(&mut self, unix_timestamp: i64) {
+        let mut clock = self.clock().await;
+        clock.unix_timestamp = unix_timestamp;
+        self.context.set_sysvar(&clock);
+    }
+}
+
+/// Whether `result` failed with `error` raised by the AMM.
+pub fn is_amm_error(result: &Result<(), TransactionError>, error: AmmError) -> bool {
+    let code = u32::from(error);
+
+    matches!(
+        result,
+        Err(TransactionError::InstructionError(_, InstructionError::Custom(c))) if *c == code
+    )
+}
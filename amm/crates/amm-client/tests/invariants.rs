@@ -0,0 +1,432 @@
+//! Randomized deposit, swap and withdraw sequences from several users, run
+//! against the compiled program on an in-process bank. Pool invariants are
+//! checked after every step. Build the program with `anchor build` first; the
+//! shared object is loaded from `target/deploy`.
+
+use amm_client::{
+    AmmProgram, CurveType, DepositArgs, InitializeAmmArgs, Pool, SwapArgs, WithdrawArgs,
+};
+use amm_math::{quote_deposit, quote_swap, quote_withdraw, Curve, U256};
+use proptest::prelude::*;
+use solana_program_test::{BanksClient, ProgramTest};
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction, hash::Hash, instruction::Instruction,
+    program_pack::Pack, pubkey::Pubkey, signature::Keypair, signer::Signer, system_instruction,
+    transaction::Transaction,
+};
+use spl_associated_token_account::instruction::create_associated_token_account;
+
+const USERS: usize = 3;
+const FEE: u16 = 30;
+const PROTOCOL_FEE: u16 = 5;
+const DECIMALS: u8 = 6;
+const USER_BALANCE: u64 = 1_000_000_000;
+const SEED_X: u64 = 100_000_000;
+const SEED_Y: u64 = 400_000_000;
+
+#[derive(Clone, Debug)]
+enum Op {
+    Deposit {
+        user: usize,
+        lp: u64,
+    },
+    Swap {
+        user: usize,
+        amount_in: u64,
+        is_x: bool,
+    },
+    /// Withdraws `share` bps of the user's LP.
+    Withdraw {
+        user: usize,
+        share: u16,
+    },
+    /// Tries to burn one LP more than the user holds.
+    Overdraw {
+        user: usize,
+    },
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        2 => (0..USERS, 1u64..100_000_000).prop_map(|(user, lp)| Op::Deposit { user, lp }),
+        4 => (0..USERS, 1u64..300_000_000, any::<bool>())
+            .prop_map(|(user, amount_in, is_x)| Op::Swap { user, amount_in, is_x }),
+        2 => (0..USERS, 1u16..=10_000).prop_map(|(user, share)| Op::Withdraw { user, share }),
+        1 => (0..USERS).prop_map(|user| Op::Overdraw { user }),
+    ]
+}
+
+/// Pool reserves and every LP holding at one point in a run.
+#[derive(Clone, Debug)]
+struct Snapshot {
+    x: u64,
+    y: u64,
+    supply: u64,
+    locked: u64,
+    lp: Vec<u64>,
+}
+
+impl Snapshot {
+    fn check(&self, previous: &Snapshot) {
+        // All LP is held by the users or locked away.
+        assert_eq!(
+            self.supply,
+            self.locked + self.lp.iter().sum::<u64>(),
+            "LP supply does not match holdings"
+        );
+
+        // k per LP share never decreases: swaps only add fees and liquidity
+        // changes are rounded in the pool's favour. With the supply unchanged
+        // this is plain k.
+        let k = U256::from(self.x) * U256::from(self.y);
+        let previous_k = U256::from(previous.x) * U256::from(previous.y);
+        assert!(
+            k * U256::from(previous.supply) * U256::from(previous.supply)
+                >= previous_k * U256::from(self.supply) * U256::from(self.supply),
+            "k per share decreased from {previous:?} to {self:?}"
+        );
+
+        // The vaults cover what every holder could withdraw.
+        let (owed_x, owed_y) = self
+            .lp
+            .iter()
+            .chain([&self.locked])
+            .map(|lp| quote_withdraw(self.x, self.y, self.supply, *lp).unwrap())
+            .fold((0u128, 0u128), |(x, y), (dx, dy)| {
+                (x + dx as u128, y + dy as u128)
+            });
+        assert!(owed_x <= self.x as u128 && owed_y <= self.y as u128);
+    }
+}
+
+struct Harness {
+    banks: BanksClient,
+    payer: Keypair,
+    blockhash: Hash,
+    nonce: u32,
+    pool: Pool,
+    users: Vec<Keypair>,
+}
+
+impl Harness {
+    async fn new() -> Self {
+        std::env::set_var(
+            "SBF_OUT_DIR",
+            concat!(env!("CARGO_MANIFEST_DIR"), "/../../target/deploy"),
+        );
+        let mut program_test = ProgramTest::new("amm", AmmProgram::id(), None);
+        program_test.prefer_bpf(true);
+        let (banks, payer, blockhash) = program_test.start().await;
+
+        let mut mints = [Keypair::new(), Keypair::new()];
+        mints.sort_by_key(|mint| mint.pubkey());
+        let vault_x = Keypair::new();
+        let vault_y = Keypair::new();
+
+        let mut harness = Self {
+            banks,
+            payer,
+            blockhash,
+            nonce: 0,
+            pool: Pool::new(
+                0,
+                mints[0].pubkey(),
+                mints[1].pubkey(),
+                vault_x.pubkey(),
+                vault_y.pubkey(),
+                spl_token::id(),
+            ),
+            users: (0..USERS).map(|_| Keypair::new()).collect(),
+        };
+
+        for mint in &mints {
+            harness.create_mint(mint).await;
+        }
+
+        let args = InitializeAmmArgs {
+            seed: 0,
+            fee: FEE,
+            protocol_fee: PROTOCOL_FEE,
+            curve: CurveType::ConstantProduct,
+            amp: 0,
+            flash_loan_fee: 0,
+        };
+        let ix = harness.pool.initialize_amm(&harness.payer.pubkey(), args);
+        assert!(harness.send(&[ix], &[&vault_x, &vault_y]).await);
+
+        for i in 0..USERS {
+            harness.fund(i).await;
+        }
+
+        let ix = harness.pool.deposit(
+            &harness.users[0].pubkey(),
+            DepositArgs {
+                amount: 1,
+                max_x: SEED_X,
+                max_y: SEED_Y,
+                expiration: i64::MAX,
+                max_slot: None,
+            },
+        );
+        let user = harness.users[0].insecure_clone();
+        assert!(harness.send(&[ix], &[&user]).await);
+
+        harness
+    }
+
+    /// Sends `ixs` paid for by the payer. Returns whether the transaction
+    /// succeeded.
+    async fn send(&mut self, ixs: &[Instruction], signers: &[&Keypair]) -> bool {
+        // A distinct compute limit keeps repeated identical instructions from
+        // being rejected as already processed.
+        self.nonce += 1;
+        let mut instructions = vec![ComputeBudgetInstruction::set_compute_unit_limit(
+            400_000 + self.nonce,
+        )];
+        instructions.extend_from_slice(ixs);
+
+        let mut all_signers = vec![&self.payer];
+        all_signers.extend_from_slice(signers);
+
+        let transaction = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&self.payer.pubkey()),
+            &all_signers,
+            self.blockhash,
+        );
+
+        self.banks.process_transaction(transaction).await.is_ok()
+    }
+
+    async fn create_mint(&mut self, mint: &Keypair) {
+        let rent = self.banks.get_rent().await.unwrap();
+        let ixs = [
+            system_instruction::create_account(
+                &self.payer.pubkey(),
+                &mint.pubkey(),
+                rent.minimum_balance(spl_token::state::Mint::LEN),
+                spl_token::state::Mint::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_mint2(
+                &spl_token::id(),
+                &mint.pubkey(),
+                &self.payer.pubkey(),
+                None,
+                DECIMALS,
+            )
+            .unwrap(),
+        ];
+
+        assert!(self.send(&ixs, &[mint]).await);
+    }
+
+    /// Gives user `i` lamports and `USER_BALANCE` of both tokens.
+    async fn fund(&mut self, i: usize) {
+        let user = self.users[i].pubkey();
+        let payer = self.payer.pubkey();
+
+        let mut ixs = vec![system_instruction::transfer(&payer, &user, 1_000_000_000)];
+        for mint in [self.pool.mint_x, self.pool.mint_y] {
+            ixs.push(create_associated_token_account(
+                &payer,
+                &user,
+                &mint,
+                &spl_token::id(),
+            ));
+            ixs.push(
+                spl_token::instruction::mint_to(
+                    &spl_token::id(),
+                    &mint,
+                    &self.pool.ata(&user, &mint),
+                    &payer,
+                    &[],
+                    USER_BALANCE,
+                )
+                .unwrap(),
+            );
+        }
+
+        assert!(self.send(&ixs, &[]).await);
+    }
+
+    async fn token_balance(&mut self, address: Pubkey) -> u64 {
+        match self.banks.get_account(address).await.unwrap() {
+            Some(account) => {
+                spl_token::state::Account::unpack(&account.data)
+                    .unwrap()
+                    .amount
+            }
+            None => 0,
+        }
+    }
+
+    async fn balances(&mut self, i: usize) -> (u64, u64, u64) {
+        let user = self.users[i].pubkey();
+        let (mint_x, mint_y, mint_lp) = (self.pool.mint_x, self.pool.mint_y, self.pool.mint_lp());
+
+        (
+            self.token_balance(self.pool.ata(&user, &mint_x)).await,
+            self.token_balance(self.pool.ata(&user, &mint_y)).await,
+            self.token_balance(self.pool.ata(&user, &mint_lp)).await,
+        )
+    }
+
+    async fn snapshot(&mut self) -> Snapshot {
+        let mint_lp = self.pool.mint_lp();
+        let account = self.banks.get_account(mint_lp).await.unwrap().unwrap();
+        let supply = spl_token::state::Mint::unpack(&account.data)
+            .unwrap()
+            .supply;
+
+        let mut lp = vec![];
+        for i in 0..USERS {
+            lp.push(self.balances(i).await.2);
+        }
+
+        Snapshot {
+            x: self.token_balance(self.pool.vault_x).await,
+            y: self.token_balance(self.pool.vault_y).await,
+            supply,
+            locked: self
+                .token_balance(AmmProgram::lp_lock(&self.pool.config))
+                .await,
+            lp,
+        }
+    }
+
+    async fn apply(&mut self, op: &Op, state: &Snapshot) {
+        match *op {
+            Op::Deposit { user, lp } => {
+                let (x, y, held) = self.balances(user).await;
+                let (dx, dy) = quote_deposit(state.x, state.y, state.supply, lp).unwrap();
+                let expected = dx > 0 && dy > 0 && dx <= x && dy <= y;
+
+                let ix = self.pool.deposit(
+                    &self.users[user].pubkey(),
+                    DepositArgs {
+                        amount: lp,
+                        max_x: x.max(1),
+                        max_y: y.max(1),
+                        expiration: i64::MAX,
+                        max_slot: None,
+                    },
+                );
+                let signer = self.users[user].insecure_clone();
+                assert_eq!(self.send(&[ix], &[&signer]).await, expected, "{op:?}");
+
+                if expected {
+                    assert_eq!(self.balances(user).await, (x - dx, y - dy, held + lp));
+                }
+            }
+            Op::Swap {
+                user,
+                amount_in,
+                is_x,
+            } => {
+                let (x, y, held) = self.balances(user).await;
+                let (reserve_in, reserve_out, balance_in) = match is_x {
+                    true => (state.x, state.y, x),
+                    false => (state.y, state.x, y),
+                };
+                let quote = quote_swap(
+                    Curve::ConstantProduct,
+                    reserve_in,
+                    reserve_out,
+                    amount_in,
+                    FEE,
+                    PROTOCOL_FEE,
+                )
+                .unwrap();
+                let expected = amount_in <= balance_in && quote.amount_out > 0;
+
+                let ix = self.pool.swap(
+                    &self.users[user].pubkey(),
+                    SwapArgs {
+                        amount_in,
+                        min_out: 1,
+                        expiration: i64::MAX,
+                        max_slot: None,
+                        is_x,
+                    },
+                );
+                let signer = self.users[user].insecure_clone();
+                assert_eq!(self.send(&[ix], &[&signer]).await, expected, "{op:?}");
+
+                // The program settles exactly the off-chain quote.
+                if expected {
+                    let after = match is_x {
+                        true => (x - amount_in, y + quote.amount_out, held),
+                        false => (x + quote.amount_out, y - amount_in, held),
+                    };
+                    assert_eq!(self.balances(user).await, after);
+                }
+            }
+            Op::Withdraw { user, share } => {
+                let (x, y, held) = self.balances(user).await;
+                let lp = (held as u128 * share as u128 / 10_000) as u64;
+                let (owed_x, owed_y) = quote_withdraw(state.x, state.y, state.supply, lp).unwrap();
+                let expected = lp > 0 && owed_x > 0 && owed_y > 0;
+
+                let ix = self.pool.withdraw(
+                    &self.users[user].pubkey(),
+                    WithdrawArgs {
+                        amount: lp,
+                        min_x: 1,
+                        min_y: 1,
+                        expiration: i64::MAX,
+                        max_slot: None,
+                    },
+                );
+                let signer = self.users[user].insecure_clone();
+                assert_eq!(self.send(&[ix], &[&signer]).await, expected, "{op:?}");
+
+                // Users are paid their pro-rata share, rounded down, and
+                // never more.
+                if expected {
+                    let (x_after, y_after, held_after) = self.balances(user).await;
+                    assert_eq!((x_after - x, y_after - y), (owed_x, owed_y));
+                    assert_eq!(held_after, held - lp);
+                }
+            }
+            Op::Overdraw { user } => {
+                let held = self.balances(user).await.2;
+
+                let ix = self.pool.withdraw(
+                    &self.users[user].pubkey(),
+                    WithdrawArgs {
+                        amount: held + 1,
+                        min_x: 1,
+                        min_y: 1,
+                        expiration: i64::MAX,
+                        max_slot: None,
+                    },
+                );
+                let signer = self.users[user].insecure_clone();
+                assert!(!self.send(&[ix], &[&signer]).await, "{op:?}");
+            }
+        }
+    }
+}
+
+async fn run(ops: Vec<Op>) {
+    let mut harness = Harness::new().await;
+    let mut state = harness.snapshot().await;
+
+    for op in &ops {
+        harness.apply(op, &state).await;
+
+        let next = harness.snapshot().await;
+        next.check(&state);
+        state = next;
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(16))]
+
+    #[test]
+    fn invariants_hold_across_random_sequences(ops in prop::collection::vec(op(), 1..40)) {
+        tokio::runtime::Runtime::new().unwrap().block_on(run(ops));
+    }
+}
@@ -0,0 +1,341 @@
+//! Migrates a pool written in the original `Config` layout, as pools created
+//! before per-pool auth PDAs are on chain, and trades against it afterwards.
+
+mod common;
+
+use amm::{AmmError, LegacyConfig, CONFIG_VERSION};
+use amm_client::{AmmProgram, Pool, WithdrawArgs};
+use amm_math::quote_withdraw;
+use anchor_lang::{AnchorSerialize, Discriminator, Space};
+use common::{is_amm_error, TestBank};
+use solana_program_test::ProgramTest;
+use solana_sdk::{
+    account::Account,
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::Keypair,
+    signer::Signer,
+};
+
+const FEE: u16 = 30;
+const RESERVE_X: u64 = 50_000_000;
+const RESERVE_Y: u64 = 200_000_000;
+const LP_SUPPLY: u64 = 100_000_000;
+
+fn token_account(mint: Pubkey, owner: Pubkey, amount: u64) -> Account {
+    let mut data = vec![0; spl_token::state::Account::LEN];
+    spl_token::state::Account {
+        mint,
+        owner,
+        amount,
+        state: spl_token::state::AccountState::Initialized,
+        ..Default::default()
+    }
+    .pack_into_slice(&mut data);
+
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: spl_token::id(),
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn mint(authority: Option<Pubkey>, supply: u64, decimals: u8) -> Account {
+    let mut data = vec![0; spl_token::state::Mint::LEN];
+    spl_token::state::Mint {
+        mint_authority: COption::from(authority),
+        supply,
+        decimals,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    }
+    .pack_into_slice(&mut data);
+
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: spl_token::id(),
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// Deploys the AMM under the upgradeable loader with `upgrade_authority`, so
+/// that `migrate_auth` can find its program data.
+fn add_upgradeable_program(program_test: &mut ProgramTest, upgrade_authority: Pubkey) {
+    let elf = std::fs::read(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../../target/deploy/amm.so"
+    ))
+    .unwrap();
+    let program_data = AmmProgram::program_data();
+
+    let program = bincode::serialize(&UpgradeableLoaderState::Program {
+        programdata_address: program_data,
+    })
+    .unwrap();
+    let mut data = bincode::serialize(&UpgradeableLoaderState::ProgramData {
+        slot: 0,
+        upgrade_authority_address: Some(upgrade_authority),
+    })
+    .unwrap();
+    data.resize(UpgradeableLoaderState::size_of_programdata_metadata(), 0);
+    data.extend_from_slice(&elf);
+
+    program_test.add_account(
+        AmmProgram::id(),
+        Account {
+            lamports: Rent::default().minimum_balance(program.len()),
+            data: program,
+            owner: bpf_loader_upgradeable::id(),
+            executable: true,
+            rent_epoch: 0,
+        },
+    );
+    program_test.add_account(
+        program_data,
+        Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: bpf_loader_upgradeable::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+}
+
+struct LegacyPool {
+    bank: TestBank,
+    pool: Pool,
+    admin: Keypair,
+    holder: Keypair,
+}
+
+/// A pool as the original program left it: its config in the legacy layout
+/// and its LP mint and authority on the program-wide `[b"auth"]` PDA. Legacy
+/// pools did not have to order their mints; `reversed` puts the greater one
+/// first.
+async fn legacy_pool(reversed: bool) -> LegacyPool {
+    let admin = Keypair::new();
+    let holder = Keypair::new();
+
+    let mut program_test = ProgramTest::default();
+    program_test.prefer_bpf(true);
+    add_upgradeable_program(&mut program_test, admin.pubkey());
+
+    let mut mints = [Pubkey::new_unique(), Pubkey::new_unique()];
+    mints.sort();
+    if reversed {
+        mints.reverse();
+    }
+    let [mint_x, mint_y] = mints;
+    let seed = 7u64;
+    let pool = Pool::new(
+        seed,
+        mint_x,
+        mint_y,
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        spl_token::id(),
+    );
+
+    let (_, config_bump) = Pubkey::find_program_address(
+        &[
+            b"config",
+            seed.to_le_bytes().as_ref(),
+            mint_x.as_ref(),
+            mint_y.as_ref(),
+        ],
+        &AmmProgram::id(),
+    );
+    let (_, lp_mint_bump) =
+        Pubkey::find_program_address(&[b"lp", pool.config.as_ref()], &AmmProgram::id());
+    let (legacy_auth, auth_bump) = Pubkey::find_program_address(&[b"auth"], &AmmProgram::id());
+
+    let mut config = amm::Config::DISCRIMINATOR.to_vec();
+    config.extend(
+        LegacyConfig {
+            seed,
+            authority: legacy_auth,
+            mint_x,
+            mint_y,
+            fee: FEE,
+            locked: false,
+            config_bump,
+            lp_mint_bump,
+            auth_bump,
+        }
+        .try_to_vec()
+        .unwrap(),
+    );
+    assert_eq!(config.len(), LegacyConfig::SPACE);
+
+    program_test.add_account(
+        pool.config,
+        Account {
+            lamports: Rent::default().minimum_balance(config.len()),
+            data: config,
+            owner: AmmProgram::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    program_test.add_account(mint_x, mint(None, RESERVE_X, 6));
+    program_test.add_account(mint_y, mint(None, RESERVE_Y, 6));
+    program_test.add_account(pool.mint_lp(), mint(Some(legacy_auth), LP_SUPPLY, 0));
+    program_test.add_account(pool.vault_x, token_account(mint_x, pool.config, RESERVE_X));
+    program_test.add_account(pool.vault_y, token_account(mint_y, pool.config, RESERVE_Y));
+    program_test.add_account(
+        pool.ata(&holder.pubkey(), &pool.mint_lp()),
+        token_account(pool.mint_lp(), holder.pubkey(), LP_SUPPLY),
+    );
+    program_test.add_account(
+        pool.ata(&holder.pubkey(), &mint_x),
+        token_account(mint_x, holder.pubkey(), 0),
+    );
+    program_test.add_account(
+        pool.ata(&holder.pubkey(), &mint_y),
+        token_account(mint_y, holder.pubkey(), 0),
+    );
+
+    let mut bank = TestBank::start(program_test).await;
+    let payer = bank.payer();
+    let ix = solana_sdk::system_instruction::transfer(&payer, &admin.pubkey(), 1_000_000_000);
+    bank.send(&[ix], &[]).await.unwrap();
+    let ix = solana_sdk::system_instruction::transfer(&payer, &holder.pubkey(), 1_000_000_000);
+    bank.send(&[ix], &[]).await.unwrap();
+
+    LegacyPool {
+        bank,
+        pool,
+        admin,
+        holder,
+    }
+}
+
+#[tokio::test]
+async fn migrates_a_legacy_pool() {
+    let LegacyPool {
+        mut bank,
+        pool,
+        admin,
+        holder,
+    } = legacy_pool(false).await;
+
+    // Only the upgrade authority may take over a legacy pool.
+    let impostor = Keypair::new();
+    let payer = bank.payer();
+    let ix = solana_sdk::system_instruction::transfer(&payer, &impostor.pubkey(), 1_000_000_000);
+    bank.send(&[ix], &[]).await.unwrap();
+    let result = bank
+        .send(&[pool.migrate_auth(&impostor.pubkey())], &[&impostor])
+        .await;
+    assert!(is_amm_error(&result, AmmError::InvalidAuthority));
+
+    bank.send(&[pool.migrate_auth(&admin.pubkey())], &[&admin])
+        .await
+        .unwrap();
+
+    let account = bank
+        .context
+        .banks_client
+        .get_account(pool.config)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.data.len(), amm::Config::INIT_SPACE);
+    assert!(account.lamports >= Rent::default().minimum_balance(account.data.len()));
+
+    let config = bank.config(&pool).await;
+    assert_eq!(config.version, CONFIG_VERSION);
+    assert_eq!(config.authority, admin.pubkey());
+    assert_eq!(config.vault_x, pool.vault_x);
+    assert_eq!(config.vault_y, pool.vault_y);
+    assert_eq!(config.fee, FEE);
+    assert_eq!(config.protocol_fee, 0);
+    assert!(!config.locked);
+
+    let lp_mint = bank
+        .context
+        .banks_client
+        .get_account(pool.mint_lp())
+        .await
+        .unwrap()
+        .unwrap();
+    let lp_mint = spl_token::state::Mint::unpack(&lp_mint.data).unwrap();
+    assert_eq!(
+        lp_mint.mint_authority,
+        COption::Some(AmmProgram::auth(&pool.config))
+    );
+
+    // A second run finds nothing left to migrate.
+    assert!(bank
+        .send(&[pool.migrate_auth(&admin.pubkey())], &[&admin])
+        .await
+        .is_err());
+
+    // LPs of the legacy pool withdraw through the current instructions.
+    let lp = LP_SUPPLY / 4;
+    let (owed_x, owed_y) = quote_withdraw(RESERVE_X, RESERVE_Y, LP_SUPPLY, lp).unwrap();
+    let ix = pool.withdraw(
+        &holder.pubkey(),
+        WithdrawArgs {
+            amount: lp,
+            min_x: owed_x,
+            min_y: owed_y,
+            expiration: i64::MAX,
+            max_slot: None,
+        },
+    );
+    bank.send(&[ix], &[&holder]).await.unwrap();
+
+    assert_eq!(
+        bank.balance(&pool.ata(&holder.pubkey(), &pool.mint_x))
+            .await,
+        owed_x
+    );
+    assert_eq!(
+        bank.balance(&pool.ata(&holder.pubkey(), &pool.mint_y))
+            .await,
+        owed_y
+    );
+    assert_eq!(
+        bank.reserves(&pool).await,
+        (RESERVE_X - owed_x, RESERVE_Y - owed_y, LP_SUPPLY - lp)
+    );
+}
+
+#[tokio::test]
+async fn indexes_a_reversed_legacy_pool_by_sorted_mints() {
+    let LegacyPool {
+        mut bank,
+        pool,
+        admin,
+        ..
+    } = legacy_pool(true).await;
+    assert!(pool.mint_x > pool.mint_y);
+
+    bank.send(&[pool.migrate_auth(&admin.pubkey())], &[&admin])
+        .await
+        .unwrap();
+
+    // The index is the one pools created on the same mints share.
+    let pair = bank.pair_index(&pool.mint_y, &pool.mint_x).await;
+    assert_eq!(pair.mint_x, pool.mint_y);
+    assert_eq!(pair.mint_y, pool.mint_x);
+    assert!(pair.pools.iter().any(|entry| entry.config == pool.config));
+
+    // Delisting finds it there too.
+    let registry = bank.registry().await;
+    bank.send(&[pool.freeze_pool(&admin.pubkey())], &[&admin])
+        .await
+        .unwrap();
+    assert_eq!(bank.registry().await.pools, registry.pools - 1);
+    let pair = bank.pair_index(&pool.mint_y, &pool.mint_x).await;
+    assert!(pair.pools.is_empty());
+}
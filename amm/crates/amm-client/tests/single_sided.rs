@@ -0,0 +1,157 @@
+//! Single-sided deposits and withdrawals settle exactly the off-chain quotes
+//! built from `amm-math`.
+
+mod common;
+
+use amm::AmmError;
+use amm_client::{AmmProgram, DepositSingleArgs, Pool, WithdrawSingleArgs};
+use amm_math::{
+    quote_deposit, quote_deposit_lp, quote_swap, quote_withdraw, zap_swap_amount, Curve,
+};
+use common::{is_amm_error, PoolOptions, TestBank};
+use solana_sdk::{signature::Keypair, signer::Signer};
+
+const FEE: u16 = 30;
+const PROTOCOL_FEE: u16 = 5;
+const BALANCE: u64 = 1_000_000_000;
+const SEED_X: u64 = 100_000_000;
+const SEED_Y: u64 = 400_000_000;
+
+/// A constant-product pool seeded by `provider`, and a second user holding
+/// both tokens.
+async fn seeded_pool() -> (TestBank, Pool, Keypair, Keypair) {
+    let mut bank = TestBank::new().await;
+    let pool = bank
+        .create_pool(PoolOptions {
+            fee: FEE,
+            protocol_fee: PROTOCOL_FEE,
+            ..Default::default()
+        })
+        .await;
+
+    let provider = Keypair::new();
+    let user = Keypair::new();
+    bank.fund_pool(&pool, &[&provider, &user], BALANCE).await;
+    bank.seed_pool(&pool, &provider, SEED_X, SEED_Y).await;
+
+    (bank, pool, provider, user)
+}
+
+/// `(x, y, lp)` balances of `user` in `pool`.
+async fn holdings(bank: &mut TestBank, pool: &Pool, user: &Keypair) -> (u64, u64, u64) {
+    let user = user.pubkey();
+
+    (
+        bank.balance(&pool.ata(&user, &pool.mint_x)).await,
+        bank.balance(&pool.ata(&user, &pool.mint_y)).await,
+        bank.balance(&pool.ata(&user, &pool.mint_lp())).await,
+    )
+}
+
+#[tokio::test]
+async fn deposit_single_mints_the_quoted_lp_and_refunds_the_excess() {
+    let (mut bank, pool, _, user) = seeded_pool().await;
+    let (x, y, supply) = bank.reserves(&pool).await;
+    let amount_in = 10_000_000;
+
+    let swap_in = zap_swap_amount(x, amount_in, FEE + PROTOCOL_FEE).unwrap();
+    let quote = quote_swap(Curve::ConstantProduct, x, y, swap_in, FEE, PROTOCOL_FEE).unwrap();
+    let (x_swapped, y_swapped) = quote.reserves_after(x, y, true).unwrap();
+    let lp = quote_deposit_lp(
+        x_swapped,
+        y_swapped,
+        supply,
+        amount_in - swap_in,
+        quote.amount_out,
+    )
+    .unwrap();
+    let (dx, dy) = quote_deposit(x_swapped, y_swapped, supply, lp).unwrap();
+    let refund = quote.amount_out - dy;
+
+    let deposit = |min_lp| {
+        pool.deposit_single(
+            &user.pubkey(),
+            DepositSingleArgs {
+                amount_in,
+                min_lp,
+                expiration: i64::MAX,
+                max_slot: None,
+                is_x: true,
+            },
+        )
+    };
+
+    let result = bank.send(&[deposit(lp + 1)], &[&user]).await;
+    assert!(is_amm_error(&result, AmmError::SlippageExceeded));
+
+    let before = holdings(&mut bank, &pool, &user).await;
+    bank.send(&[deposit(lp)], &[&user]).await.unwrap();
+    let after = holdings(&mut bank, &pool, &user).await;
+
+    // The user pays all of `amount_in` and gets back the X and Y the
+    // balanced deposit did not need.
+    assert_eq!(after.0, before.0 - swap_in - dx);
+    assert_eq!(after.1, before.1 + refund);
+    assert_eq!(after.2, before.2 + lp);
+    assert!(swap_in + dx <= amount_in);
+
+    assert_eq!(
+        bank.reserves(&pool).await,
+        (x_swapped + dx, y_swapped + dy, supply + lp)
+    );
+    assert_eq!(
+        bank.balance(&AmmProgram::fee_vault_x(&pool.config)).await,
+        quote.protocol_fee
+    );
+}
+
+#[tokio::test]
+async fn withdraw_single_pays_the_withdrawal_plus_the_swapped_side() {
+    let (mut bank, pool, provider, _) = seeded_pool().await;
+    let (x, y, supply) = bank.reserves(&pool).await;
+    let before = holdings(&mut bank, &pool, &provider).await;
+    let lp = before.2 / 3;
+
+    // The Y share is swapped back into the pool for X after the pro-rata
+    // amounts have left it.
+    let (amount_x, amount_y) = quote_withdraw(x, y, supply, lp).unwrap();
+    let quote = quote_swap(
+        Curve::ConstantProduct,
+        y - amount_y,
+        x - amount_x,
+        amount_y,
+        FEE,
+        PROTOCOL_FEE,
+    )
+    .unwrap();
+    let out = amount_x + quote.amount_out;
+
+    let withdraw = |min_out| {
+        pool.withdraw_single(
+            &provider.pubkey(),
+            WithdrawSingleArgs {
+                amount: lp,
+                min_out,
+                expiration: i64::MAX,
+                max_slot: None,
+                is_x: true,
+            },
+        )
+    };
+
+    let result = bank.send(&[withdraw(out + 1)], &[&provider]).await;
+    assert!(is_amm_error(&result, AmmError::SlippageExceeded));
+
+    bank.send(&[withdraw(out)], &[&provider]).await.unwrap();
+    let after = holdings(&mut bank, &pool, &provider).await;
+
+    assert_eq!(after, (before.0 + out, before.1, before.2 - lp));
+    assert_eq!(
+        bank.reserves(&pool).await,
+        (x - out, y - quote.protocol_fee, supply - lp)
+    );
+    assert_eq!(
+        bank.balance(&AmmProgram::fee_vault_y(&pool.config)).await,
+        quote.protocol_fee
+    );
+}
@@ -0,0 +1,147 @@
+//! StableSwap pools price swaps on the amplified invariant, with the
+//! amplification in effect at the time of the swap.
+
+mod common;
+
+use amm::{AmmError, MIN_RAMP_DURATION};
+use amm_client::{AmmProgram, CurveType, Pool, RampAmpArgs, SwapArgs};
+use amm_math::{quote_swap, stable_amount_out, Curve};
+use common::{is_amm_error, PoolOptions, TestBank};
+use solana_sdk::{signature::Keypair, signer::Signer};
+
+const FEE: u16 = 4;
+const PROTOCOL_FEE: u16 = 1;
+const AMP: u64 = 100;
+const BALANCE: u64 = 1_000_000_000;
+const SEED: u64 = 100_000_000;
+
+/// A StableSwap pool seeded with equal reserves, and a user holding both
+/// tokens. The payer is the pool authority.
+async fn stable_pool() -> (TestBank, Pool, Keypair) {
+    let mut bank = TestBank::new().await;
+    let pool = bank
+        .create_pool(PoolOptions {
+            curve: CurveType::StableSwap,
+            amp: AMP,
+            fee: FEE,
+            protocol_fee: PROTOCOL_FEE,
+            ..Default::default()
+        })
+        .await;
+
+    let user = Keypair::new();
+    bank.fund_pool(&pool, &[&user], BALANCE).await;
+    bank.seed_pool(&pool, &user, SEED, SEED).await;
+
+    (bank, pool, user)
+}
+
+/// Swaps `amount_in` of X for Y and checks the payout against the invariant
+/// at `amp`, net of fees.
+async fn swap_at(bank: &mut TestBank, pool: &Pool, user: &Keypair, amount_in: u64, amp: u64) {
+    let (x, y, supply) = bank.reserves(pool).await;
+    let user_y = pool.ata(&user.pubkey(), &pool.mint_y);
+    let fee_vault = AmmProgram::fee_vault_x(&pool.config);
+
+    let fee = amount_in * (FEE + PROTOCOL_FEE) as u64 / 10_000;
+    let amount_out = stable_amount_out(x, y, amount_in - fee, amp).unwrap();
+    let quote = quote_swap(
+        Curve::StableSwap { amp },
+        x,
+        y,
+        amount_in,
+        FEE,
+        PROTOCOL_FEE,
+    )
+    .unwrap();
+    assert_eq!(quote.amount_out, amount_out);
+
+    let balance = bank.balance(&user_y).await;
+    let fees = bank.balance(&fee_vault).await;
+
+    let ix = pool.swap(
+        &user.pubkey(),
+        SwapArgs {
+            amount_in,
+            min_out: amount_out,
+            expiration: i64::MAX,
+            max_slot: None,
+            is_x: true,
+        },
+    );
+    bank.send(&[ix], &[user]).await.unwrap();
+
+    assert_eq!(bank.balance(&user_y).await, balance + amount_out);
+    assert_eq!(
+        bank.reserves(pool).await,
+        (x + amount_in - quote.protocol_fee, y - amount_out, supply)
+    );
+    assert_eq!(bank.balance(&fee_vault).await, fees + quote.protocol_fee);
+}
+
+#[tokio::test]
+async fn swap_pays_out_along_the_invariant() {
+    let (mut bank, pool, user) = stable_pool().await;
+    let amount_in = 10_000_000;
+
+    // Around the peg the amplified curve gives far less slippage than a
+    // constant product on the same reserves.
+    let (x, y, _) = bank.reserves(&pool).await;
+    let constant_product =
+        quote_swap(Curve::ConstantProduct, x, y, amount_in, FEE, PROTOCOL_FEE).unwrap();
+    let stable = quote_swap(
+        Curve::StableSwap { amp: AMP },
+        x,
+        y,
+        amount_in,
+        FEE,
+        PROTOCOL_FEE,
+    )
+    .unwrap();
+    assert!(stable.amount_out > constant_product.amount_out);
+
+    swap_at(&mut bank, &pool, &user, amount_in, AMP).await;
+}
+
+#[tokio::test]
+async fn amp_ramps_linearly_to_its_target() {
+    let (mut bank, pool, user) = stable_pool().await;
+    let authority = bank.payer();
+    let target_amp = AMP * 10;
+    let start = bank.clock().await.unix_timestamp;
+    let stop = start + 2 * MIN_RAMP_DURATION;
+
+    // Ramps must be slow and bounded.
+    let ramp = |target_amp, stop_ts| {
+        pool.ramp_amp(
+            &authority,
+            RampAmpArgs {
+                target_amp,
+                stop_ts,
+            },
+        )
+    };
+    let result = bank
+        .send(&[ramp(target_amp, start + MIN_RAMP_DURATION - 1)], &[])
+        .await;
+    assert!(is_amm_error(&result, AmmError::InvalidAmp));
+    let result = bank.send(&[ramp(target_amp + 1, stop)], &[]).await;
+    assert!(is_amm_error(&result, AmmError::InvalidAmp));
+
+    bank.send(&[ramp(target_amp, stop)], &[]).await.unwrap();
+    let config = bank.config(&pool).await;
+    assert_eq!((config.initial_amp, config.target_amp), (AMP, target_amp));
+    let start = config.ramp_start_ts;
+
+    // Halfway through, the pool prices on the midpoint amplification.
+    let halfway = start + (stop - start) / 2;
+    bank.warp_to_timestamp(halfway).await;
+    let amp = AMP + (target_amp - AMP) * (halfway - start) as u64 / (stop - start) as u64;
+    assert_eq!(bank.config(&pool).await.amp(halfway), amp);
+    swap_at(&mut bank, &pool, &user, 10_000_000, amp).await;
+
+    // Past the stop, the target holds.
+    bank.warp_to_timestamp(stop + 1).await;
+    assert_eq!(bank.config(&pool).await.amp(stop + 1), target_amp);
+    swap_at(&mut bank, &pool, &user, 10_000_000, target_amp).await;
+}
@@ -0,0 +1,188 @@
+//! Multi-hop `swap_route` settles the chained quotes of its pools, and routes
+//! that do not chain or reuse a pool are rejected.
+
+mod common;
+
+use amm::AmmError;
+use amm_client::{AmmProgram, Pool, SwapRouteArgs};
+use amm_math::{quote_swap, Curve};
+use common::{is_amm_error, PoolOptions, TestBank};
+use solana_sdk::{signature::Keypair, signer::Signer};
+
+const FEE: u16 = 30;
+const PROTOCOL_FEE: u16 = 5;
+const BALANCE: u64 = 1_000_000_000;
+
+/// Accounts of `SwapRoute` ahead of the per-hop accounts.
+const ROUTE_ACCOUNTS: usize = 4;
+
+/// Offset of `vault_in` within a hop's accounts.
+const VAULT_IN: usize = 3;
+
+/// Pools on A/B and B/C, with mints sorted A < B < C, and a user holding all
+/// three tokens.
+struct Route {
+    bank: TestBank,
+    ab: Pool,
+    bc: Pool,
+    user: Keypair,
+}
+
+async fn route() -> Route {
+    let mut bank = TestBank::new().await;
+
+    let mut mints = [Keypair::new(), Keypair::new(), Keypair::new()];
+    mints.sort_by_key(|mint| mint.pubkey());
+    for mint in &mints {
+        bank.create_mint(mint, &spl_token::id(), None).await;
+    }
+    let [a, b, c] = mints.map(|mint| mint.pubkey());
+
+    let options = PoolOptions {
+        fee: FEE,
+        protocol_fee: PROTOCOL_FEE,
+        ..Default::default()
+    };
+    let ab = bank.create_pool_on(options, &a, &b).await;
+    let bc = bank.create_pool_on(options, &b, &c).await;
+
+    let provider = Keypair::new();
+    let user = Keypair::new();
+    for owner in [&provider, &user] {
+        bank.fund(&owner.pubkey(), &[a, b, c], BALANCE, &spl_token::id())
+            .await;
+    }
+
+    bank.seed_pool(&ab, &provider, 100_000_000, 300_000_000)
+        .await;
+    bank.seed_pool(&bc, &provider, 200_000_000, 50_000_000)
+        .await;
+
+    Route { bank, ab, bc, user }
+}
+
+fn args(amount_in: u64, min_out: u64) -> SwapRouteArgs {
+    SwapRouteArgs {
+        amount_in,
+        min_out,
+        expiration: i64::MAX,
+        max_slot: None,
+    }
+}
+
+#[tokio::test]
+async fn routes_through_consecutive_pools() {
+    let Route {
+        mut bank,
+        ab,
+        bc,
+        user,
+    } = route().await;
+    let (ab_x, ab_y, _) = bank.reserves(&ab).await;
+    let (bc_x, bc_y, _) = bank.reserves(&bc).await;
+    let amount_in = 5_000_000;
+
+    // A to B through A/B, then B to C through B/C.
+    let first = quote_swap(
+        Curve::ConstantProduct,
+        ab_x,
+        ab_y,
+        amount_in,
+        FEE,
+        PROTOCOL_FEE,
+    )
+    .unwrap();
+    let second = quote_swap(
+        Curve::ConstantProduct,
+        bc_x,
+        bc_y,
+        first.amount_out,
+        FEE,
+        PROTOCOL_FEE,
+    )
+    .unwrap();
+
+    let user_a = ab.ata(&user.pubkey(), &ab.mint_x);
+    let user_b = ab.ata(&user.pubkey(), &ab.mint_y);
+    let user_c = bc.ata(&user.pubkey(), &bc.mint_y);
+    let hops = [(ab, true), (bc, true)];
+
+    let ix = Pool::swap_route(
+        &user.pubkey(),
+        &hops,
+        args(amount_in, second.amount_out + 1),
+    );
+    let result = bank.send(&[ix], &[&user]).await;
+    assert!(is_amm_error(&result, AmmError::SlippageExceeded));
+
+    let ix = Pool::swap_route(&user.pubkey(), &hops, args(amount_in, second.amount_out));
+    bank.send(&[ix], &[&user]).await.unwrap();
+
+    assert_eq!(bank.balance(&user_a).await, BALANCE - amount_in);
+    assert_eq!(bank.balance(&user_b).await, BALANCE);
+    assert_eq!(bank.balance(&user_c).await, BALANCE + second.amount_out);
+
+    let (ab_x_after, ab_y_after, _) = bank.reserves(&ab).await;
+    let (bc_x_after, bc_y_after, _) = bank.reserves(&bc).await;
+    assert_eq!(
+        Some((ab_x_after, ab_y_after)),
+        first.reserves_after(ab_x, ab_y, true)
+    );
+    assert_eq!(
+        Some((bc_x_after, bc_y_after)),
+        second.reserves_after(bc_x, bc_y, true)
+    );
+    assert_eq!(
+        bank.balance(&AmmProgram::fee_vault_x(&ab.config)).await,
+        first.protocol_fee
+    );
+    assert_eq!(
+        bank.balance(&AmmProgram::fee_vault_x(&bc.config)).await,
+        second.protocol_fee
+    );
+}
+
+#[tokio::test]
+async fn rejects_a_pool_used_twice() {
+    let Route {
+        mut bank, ab, user, ..
+    } = route().await;
+
+    // A to B, back to A and to B again, all through A/B.
+    let hops = [(ab, true), (ab, false), (ab, true)];
+    let ix = Pool::swap_route(&user.pubkey(), &hops, args(1_000_000, 1));
+
+    let result = bank.send(&[ix], &[&user]).await;
+    assert!(is_amm_error(&result, AmmError::InvalidRoute));
+}
+
+#[tokio::test]
+async fn rejects_a_fee_vault_as_vault_in() {
+    let Route {
+        mut bank, ab, user, ..
+    } = route().await;
+
+    // The fee vault has the same mint and authority as the pool vault.
+    let mut ix = Pool::swap_route(&user.pubkey(), &[(ab, true)], args(1_000_000, 1));
+    ix.accounts[ROUTE_ACCOUNTS + VAULT_IN].pubkey = AmmProgram::fee_vault_x(&ab.config);
+
+    let result = bank.send(&[ix], &[&user]).await;
+    assert!(is_amm_error(&result, AmmError::InvalidRoute));
+}
+
+#[tokio::test]
+async fn rejects_hops_that_do_not_chain() {
+    let Route {
+        mut bank,
+        ab,
+        bc,
+        user,
+    } = route().await;
+
+    // A/B pays out B, but the second hop sells C.
+    let hops = [(ab, true), (bc, false)];
+    let ix = Pool::swap_route(&user.pubkey(), &hops, args(1_000_000, 1));
+
+    let result = bank.send(&[ix], &[&user]).await;
+    assert!(is_amm_error(&result, AmmError::InvalidRoute));
+}
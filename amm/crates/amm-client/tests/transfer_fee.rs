@@ -0,0 +1,142 @@
+//! Pools on Token-2022 mints with a transfer fee: deposits credit the vaults
+//! with what they receive, and exact-in swaps charge the user exactly the
+//! input and price what reaches the vault.
+
+mod common;
+
+use amm::AmmError;
+use amm_client::{AmmProgram, DepositArgs, Pool, SwapArgs};
+use amm_math::{quote_deposit, quote_swap, Curve};
+use common::{is_amm_error, PoolOptions, TestBank};
+use solana_sdk::{signature::Keypair, signer::Signer};
+
+const FEE: u16 = 30;
+const PROTOCOL_FEE: u16 = 5;
+const TRANSFER_FEE_BPS: u16 = 100;
+const BALANCE: u64 = 1_000_000_000;
+const SEED_X: u64 = 100_000_000;
+const SEED_Y: u64 = 400_000_000;
+
+/// A constant-product pool on two mints withholding `TRANSFER_FEE_BPS` of
+/// every transfer, seeded by `provider`, and a second user holding both
+/// tokens.
+async fn fee_pool() -> (TestBank, Pool, Keypair, Keypair) {
+    let mut bank = TestBank::new().await;
+    let pool = bank
+        .create_pool(PoolOptions {
+            fee: FEE,
+            protocol_fee: PROTOCOL_FEE,
+            token_program: spl_token_2022::id(),
+            transfer_fee_bps: Some(TRANSFER_FEE_BPS),
+            ..Default::default()
+        })
+        .await;
+
+    let provider = Keypair::new();
+    let user = Keypair::new();
+    bank.fund_pool(&pool, &[&provider, &user], BALANCE).await;
+    bank.seed_pool(&pool, &provider, SEED_X, SEED_Y).await;
+
+    (bank, pool, provider, user)
+}
+
+#[tokio::test]
+async fn deposits_credit_what_reaches_the_vaults() {
+    let (mut bank, pool, provider, user) = fee_pool().await;
+
+    // The first deposit spends the maxima in full; the vaults keep them
+    // net of the transfer fee.
+    let seed_x = SEED_X - bank.transfer_fee(&pool.mint_x, SEED_X).await;
+    let seed_y = SEED_Y - bank.transfer_fee(&pool.mint_y, SEED_Y).await;
+    let provider_x = pool.ata(&provider.pubkey(), &pool.mint_x);
+    let provider_y = pool.ata(&provider.pubkey(), &pool.mint_y);
+    assert_eq!(bank.balance(&provider_x).await, BALANCE - SEED_X);
+    assert_eq!(bank.balance(&provider_y).await, BALANCE - SEED_Y);
+
+    let (x, y, supply) = bank.reserves(&pool).await;
+    assert_eq!((x, y), (seed_x, seed_y));
+
+    // Later deposits pay the quoted amounts plus the fee on top.
+    let lp = supply / 10;
+    let (dx, dy) = quote_deposit(x, y, supply, lp).unwrap();
+    let user_x = pool.ata(&user.pubkey(), &pool.mint_x);
+    let user_y = pool.ata(&user.pubkey(), &pool.mint_y);
+
+    let deposit = |max_x, max_y| {
+        pool.deposit(
+            &user.pubkey(),
+            DepositArgs {
+                amount: lp,
+                max_x,
+                max_y,
+                expiration: i64::MAX,
+                max_slot: None,
+            },
+        )
+    };
+
+    // Maxima covering the quote but not its transfer fee are too low.
+    let result = bank.send(&[deposit(dx, dy)], &[&user]).await;
+    assert!(is_amm_error(&result, AmmError::SlippageExceeded));
+
+    bank.send(&[deposit(BALANCE, BALANCE)], &[&user])
+        .await
+        .unwrap();
+
+    assert_eq!(bank.reserves(&pool).await, (x + dx, y + dy, supply + lp));
+
+    let paid_x = BALANCE - bank.balance(&user_x).await;
+    let paid_y = BALANCE - bank.balance(&user_y).await;
+    assert_eq!(paid_x - bank.transfer_fee(&pool.mint_x, paid_x).await, dx);
+    assert_eq!(paid_y - bank.transfer_fee(&pool.mint_y, paid_y).await, dy);
+}
+
+#[tokio::test]
+async fn exact_in_swaps_charge_exactly_the_input() {
+    let (mut bank, pool, _, user) = fee_pool().await;
+    let (x, y, supply) = bank.reserves(&pool).await;
+    let amount_in = 10_000_000;
+
+    let net_in = amount_in - bank.transfer_fee(&pool.mint_x, amount_in).await;
+    let quote = quote_swap(Curve::ConstantProduct, x, y, net_in, FEE, PROTOCOL_FEE).unwrap();
+    let received = quote.amount_out - bank.transfer_fee(&pool.mint_y, quote.amount_out).await;
+    let protocol_fee =
+        quote.protocol_fee - bank.transfer_fee(&pool.mint_x, quote.protocol_fee).await;
+
+    let swap = |min_out| {
+        pool.swap(
+            &user.pubkey(),
+            SwapArgs {
+                amount_in,
+                min_out,
+                expiration: i64::MAX,
+                max_slot: None,
+                is_x: true,
+            },
+        )
+    };
+
+    // `min_out` is checked against what the user receives.
+    let result = bank.send(&[swap(received + 1)], &[&user]).await;
+    assert!(is_amm_error(&result, AmmError::SlippageExceeded));
+
+    bank.send(&[swap(received)], &[&user]).await.unwrap();
+
+    let user_x = pool.ata(&user.pubkey(), &pool.mint_x);
+    let user_y = pool.ata(&user.pubkey(), &pool.mint_y);
+    assert_eq!(bank.balance(&user_x).await, BALANCE - amount_in);
+    assert_eq!(bank.balance(&user_y).await, BALANCE + received);
+
+    assert_eq!(
+        bank.reserves(&pool).await,
+        (
+            x + net_in - quote.protocol_fee,
+            y - quote.amount_out,
+            supply
+        )
+    );
+    assert_eq!(
+        bank.balance(&AmmProgram::fee_vault_x(&pool.config)).await,
+        protocol_fee
+    );
+}
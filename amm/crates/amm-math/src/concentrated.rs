@@ -0,0 +1,273 @@
+use crate::div_ceil;
+
+#[allow(clippy::all)]
+mod u256 {
+    uint::construct_uint! {
+        /// 256-bit integer for intermediate concentrated-liquidity products.
+        pub struct U256(4);
+    }
+}
+
+pub use u256::U256;
+
+/// Tick range of concentrated-liquidity pools. Prices span `1.0001^±MAX_TICK`,
+/// roughly `2^±32`, so sqrt prices fit in 80 bits of Q64.64.
+pub const MIN_TICK: i32 = -221_818;
+pub const MAX_TICK: i32 = 221_818;
+
+/// `2^64 / sqrt(1.0001)^(2^i)` in Q64.64, for `sqrt_price_at_tick`.
+const TICK_FACTORS: [u128; 18] = [
+    0xfffcb933bd6fad37,
+    0xfff97272373d4132,
+    0xfff2e50f5f656932,
+    0xffe5caca7e10e4e6,
+    0xffcb9843d60f6159,
+    0xff973b41fa98c081,
+    0xff2ea16466c96a38,
+    0xfe5dee046a99a2a8,
+    0xfcbe86c7900a88ae,
+    0xf987a7253ac41317,
+    0xf3392b0822b70005,
+    0xe7159475a2c29b74,
+    0xd097f3bdfd2022b8,
+    0xa9f746462d870fdf,
+    0x70d869a156d2a1b8,
+    0x31be135f97d08fd9,
+    0x09aa508b5b7a84e1,
+    0x005d6af8dedb8119,
+];
+
+/// `sqrt(1.0001^tick)` in Q64.64.
+pub fn sqrt_price_at_tick(tick: i32) -> Option<u128> {
+    if !(MIN_TICK..=MAX_TICK).contains(&tick) {
+        return None;
+    }
+
+    let abs_tick = tick.unsigned_abs();
+    let mut ratio = 1u128 << 64;
+    for (i, factor) in TICK_FACTORS.iter().enumerate() {
+        if abs_tick & (1 << i) != 0 {
+            ratio = (ratio * factor) >> 64;
+        }
+    }
+
+    match tick > 0 {
+        true => Some(u128::MAX / ratio),
+        false => Some(ratio),
+    }
+}
+
+/// Greatest tick whose sqrt price is at or below `sqrt_price`.
+pub fn tick_at_sqrt_price(sqrt_price: u128) -> Option<i32> {
+    if sqrt_price < sqrt_price_at_tick(MIN_TICK)? || sqrt_price > sqrt_price_at_tick(MAX_TICK)? {
+        return None;
+    }
+
+    let (mut low, mut high) = (MIN_TICK, MAX_TICK);
+    while low < high {
+        let mid = low + (high - low + 1) / 2;
+        match sqrt_price_at_tick(mid)? <= sqrt_price {
+            true => low = mid,
+            false => high = mid - 1,
+        }
+    }
+
+    Some(low)
+}
+
+fn mul_div(a: U256, b: U256, denominator: U256, round_up: bool) -> Option<U256> {
+    if denominator.is_zero() {
+        return None;
+    }
+
+    let product = a.checked_mul(b)?;
+    let (quotient, remainder) = product.div_mod(denominator);
+
+    match round_up && !remainder.is_zero() {
+        true => quotient.checked_add(U256::one()),
+        false => Some(quotient),
+    }
+}
+
+pub(crate) fn to_u64(value: U256) -> Option<u64> {
+    match value > U256::from(u64::MAX) {
+        true => None,
+        false => Some(value.as_u64()),
+    }
+}
+
+/// X held by `liquidity` between the sqrt prices `a` and `b`.
+pub fn amount_x_delta(a: u128, b: u128, liquidity: u128, round_up: bool) -> Option<u64> {
+    let (a, b) = (a.min(b), a.max(b));
+    if a == 0 {
+        return None;
+    }
+
+    let numerator = U256::from(liquidity) << 64;
+    let scaled = mul_div(numerator, U256::from(b - a), U256::from(b), round_up)?;
+
+    to_u64(mul_div(scaled, U256::one(), U256::from(a), round_up)?)
+}
+
+/// Y held by `liquidity` between the sqrt prices `a` and `b`.
+pub fn amount_y_delta(a: u128, b: u128, liquidity: u128, round_up: bool) -> Option<u64> {
+    let (a, b) = (a.min(b), a.max(b));
+
+    to_u64(mul_div(
+        U256::from(liquidity),
+        U256::from(b - a),
+        U256::one() << 64,
+        round_up,
+    )?)
+}
+
+/// Sqrt price after adding `amount` of the input token at `sqrt_price`.
+/// Rounded so the pool never gives away more than the input pays for.
+pub fn next_sqrt_price_from_input(
+    sqrt_price: u128,
+    liquidity: u128,
+    amount: u64,
+    is_x: bool,
+) -> Option<u128> {
+    if liquidity == 0 {
+        return None;
+    }
+
+    let next = match is_x {
+        // L * P / (L + amount * P), rounded up.
+        true => {
+            let numerator = U256::from(liquidity) << 64;
+            let denominator =
+                numerator.checked_add(U256::from(amount).checked_mul(U256::from(sqrt_price))?)?;
+            mul_div(numerator, U256::from(sqrt_price), denominator, true)?
+        }
+        // P + amount / L, rounded down.
+        false => U256::from(sqrt_price)
+            .checked_add((U256::from(amount) << 64) / U256::from(liquidity))?,
+    };
+
+    match next > U256::from(u128::MAX) {
+        true => None,
+        false => Some(next.as_u128()),
+    }
+}
+
+pub struct SwapStep {
+    pub sqrt_price_next: u128,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee: u64,
+}
+
+/// Exact-input swap of up to `amount_remaining` (fee included) within a
+/// single range of constant `liquidity`, moving the price from `sqrt_price`
+/// towards `sqrt_price_target`. `fee` is in bps and charged on the input.
+pub fn swap_step(
+    sqrt_price: u128,
+    sqrt_price_target: u128,
+    liquidity: u128,
+    amount_remaining: u64,
+    fee: u16,
+    is_x: bool,
+) -> Option<SwapStep> {
+    if fee >= 10_000 {
+        return None;
+    }
+
+    let remaining_less_fee = (amount_remaining as u128 * (10_000 - fee as u128) / 10_000) as u64;
+
+    let amount_in_to_target = match is_x {
+        true => amount_x_delta(sqrt_price_target, sqrt_price, liquidity, true)?,
+        false => amount_y_delta(sqrt_price, sqrt_price_target, liquidity, true)?,
+    };
+
+    let sqrt_price_next = match remaining_less_fee >= amount_in_to_target {
+        true => sqrt_price_target,
+        false => next_sqrt_price_from_input(sqrt_price, liquidity, remaining_less_fee, is_x)?,
+    };
+    let reached_target = sqrt_price_next == sqrt_price_target;
+
+    let (amount_in, amount_out) = match is_x {
+        true => (
+            amount_x_delta(sqrt_price_next, sqrt_price, liquidity, true)?,
+            amount_y_delta(sqrt_price_next, sqrt_price, liquidity, false)?,
+        ),
+        false => (
+            amount_y_delta(sqrt_price, sqrt_price_next, liquidity, true)?,
+            amount_x_delta(sqrt_price, sqrt_price_next, liquidity, false)?,
+        ),
+    };
+
+    let fee = match reached_target {
+        true => u64::try_from(div_ceil(
+            amount_in as u128 * fee as u128,
+            10_000 - fee as u128,
+        )?)
+        .ok()?,
+        false => amount_remaining.checked_sub(amount_in)?,
+    };
+
+    Some(SwapStep {
+        sqrt_price_next,
+        amount_in,
+        amount_out,
+        fee,
+    })
+}
+
+/// X and Y backing `liquidity` over `[tick_lower, tick_upper)` with the pool at
+/// `sqrt_price`/`tick_current`. Round up when adding liquidity and down when
+/// removing it.
+pub fn position_amounts(
+    sqrt_price: u128,
+    tick_current: i32,
+    tick_lower: i32,
+    tick_upper: i32,
+    liquidity: u128,
+    round_up: bool,
+) -> Option<(u64, u64)> {
+    let sqrt_lower = sqrt_price_at_tick(tick_lower)?;
+    let sqrt_upper = sqrt_price_at_tick(tick_upper)?;
+
+    match tick_current {
+        t if t < tick_lower => Some((
+            amount_x_delta(sqrt_lower, sqrt_upper, liquidity, round_up)?,
+            0,
+        )),
+        t if t < tick_upper => Some((
+            amount_x_delta(sqrt_price, sqrt_upper, liquidity, round_up)?,
+            amount_y_delta(sqrt_lower, sqrt_price, liquidity, round_up)?,
+        )),
+        _ => Some((
+            0,
+            amount_y_delta(sqrt_lower, sqrt_upper, liquidity, round_up)?,
+        )),
+    }
+}
+
+/// Applies a signed liquidity change.
+pub fn add_liquidity_delta(liquidity: u128, delta: i128) -> Option<u128> {
+    match delta < 0 {
+        true => liquidity.checked_sub(delta.unsigned_abs()),
+        false => liquidity.checked_add(delta as u128),
+    }
+}
+
+/// Fee growth per unit of `liquidity` from collecting `fee`, in Q64.64.
+pub fn fee_growth_delta(fee: u64, liquidity: u128) -> Option<u128> {
+    if liquidity == 0 {
+        return None;
+    }
+
+    Some(((fee as u128) << 64) / liquidity)
+}
+
+/// Fees earned by `liquidity` over a Q64.64 fee growth of `growth`.
+pub fn fees_from_growth(growth: u128, liquidity: u128) -> Option<u64> {
+    to_u64(mul_div(
+        U256::from(growth),
+        U256::from(liquidity),
+        U256::one() << 64,
+        false,
+    )?)
+}
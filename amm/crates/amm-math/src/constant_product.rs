@@ -0,0 +1,57 @@
+use crate::{div_ceil, gross_up, to_u64, U256};
+
+/// Output of a constant-product pool holding `reserve_in`/`reserve_out` for a
+/// net (fee already removed) input of `net_in`. Rounded down.
+pub fn exact_in_amount_out(reserve_in: u64, reserve_out: u64, net_in: u64) -> Option<u64> {
+    let amount_out = (reserve_out as u128)
+        .checked_mul(net_in as u128)?
+        .checked_div((reserve_in as u128).checked_add(net_in as u128)?)?;
+
+    u64::try_from(amount_out).ok()
+}
+
+/// Input, fee included, needed to take `amount_out` from a constant-product
+/// pool holding `reserve_in`/`reserve_out`, with `fee` in bps. Returns the
+/// gross input and the fee part of it, both rounded up in the pool's favour.
+pub fn exact_out_amount_in(
+    reserve_in: u64,
+    reserve_out: u64,
+    amount_out: u64,
+    fee: u16,
+) -> Option<(u64, u64)> {
+    if amount_out >= reserve_out || fee >= 10_000 {
+        return None;
+    }
+
+    let net_in = div_ceil(
+        (reserve_in as u128).checked_mul(amount_out as u128)?,
+        (reserve_out - amount_out) as u128,
+    )?;
+
+    gross_up(u64::try_from(net_in).ok()?, fee)
+}
+
+/// Amount of a single-sided deposit of `amount_in` that has to be swapped
+/// through a pool holding `reserve_in` so that the remainder and the swap
+/// output are in the pool's post-swap ratio. `fee` is the total fee in bps.
+pub fn zap_swap_amount(reserve_in: u64, amount_in: u64, fee: u16) -> Option<u64> {
+    let r = 10_000u64.checked_sub(fee as u64)?;
+    if r == 0 {
+        return None;
+    }
+
+    // `b * b` alone passes u128 once `reserve_in` is above about 9.2e14.
+    let x = U256::from(reserve_in);
+    let b = x.checked_mul(U256::from(10_000 + r))?;
+
+    let discriminant = b.checked_mul(b)?.checked_add(
+        U256::from(40_000u64)
+            .checked_mul(U256::from(r))?
+            .checked_mul(x)?
+            .checked_mul(U256::from(amount_in))?,
+    )?;
+
+    let swap_in = discriminant.integer_sqrt().checked_sub(b)? / U256::from(2 * r);
+
+    to_u64(swap_in)
+}
@@ -0,0 +1,44 @@
+use crate::U256;
+
+/// Volatility left of `volatility` after `elapsed` seconds of a linear decay
+/// to zero over `period` seconds.
+pub fn decay_volatility(volatility: u32, elapsed: i64, period: i64) -> u32 {
+    if elapsed <= 0 || period <= 0 {
+        return volatility;
+    }
+    if elapsed >= period {
+        return 0;
+    }
+
+    let decayed = volatility as u128 * elapsed as u128 / period as u128;
+
+    volatility - decayed as u32
+}
+
+/// Relative move in bps, rounded up, of the reserve price `y / x` between two
+/// pairs of reserves. Saturates at `u32::MAX`.
+pub fn price_move_bps(x_before: u64, y_before: u64, x_after: u64, y_after: u64) -> Option<u32> {
+    // (y1 / x1 - y0 / x0) / (y0 / x0) = (y1 * x0 - y0 * x1) / (x1 * y0)
+    let a = y_after as u128 * x_before as u128;
+    let b = y_before as u128 * x_after as u128;
+    let denominator = U256::from(x_after as u128 * y_before as u128);
+    if denominator.is_zero() {
+        return None;
+    }
+
+    let numerator = U256::from(a.abs_diff(b)) * U256::from(10_000u64);
+    let move_bps = (numerator + denominator - U256::one()) / denominator;
+
+    Some(match move_bps > U256::from(u32::MAX) {
+        true => u32::MAX,
+        false => move_bps.as_u32(),
+    })
+}
+
+/// LP fee in bps for a pool that has recently moved by `volatility` bps: one
+/// bps of fee per bps of movement on top of `min_fee`, capped at `max_fee`.
+pub fn dynamic_fee(min_fee: u16, max_fee: u16, volatility: u32) -> u16 {
+    let fee = (min_fee as u32).saturating_add(volatility);
+
+    fee.min(max_fee as u32).max(min_fee as u32) as u16
+}
@@ -0,0 +1,55 @@
+//! Pool math shared by the AMM program and off-chain clients, so quotes
+//! computed before sending a transaction match what the program settles.
+//!
+//! Everything here is integer arithmetic returning `None` on overflow or on
+//! inputs a pool cannot trade, and rounds in the pool's favour.
+
+mod concentrated;
+mod constant_product;
+mod dynamic_fee;
+mod quote;
+mod stable;
+
+pub use concentrated::*;
+pub use constant_product::*;
+pub use dynamic_fee::*;
+pub use quote::*;
+pub use stable::*;
+
+/// Integer square root, rounded down.
+pub fn isqrt(n: u128) -> u128 {
+    if n < 2 {
+        return n;
+    }
+
+    let mut x = n;
+    let mut y = (x + 1) / 2;
+    while y < x {
+        x = y;
+        y = (x + n / x) / 2;
+    }
+
+    x
+}
+
+/// `a / b` rounded up.
+pub fn div_ceil(a: u128, b: u128) -> Option<u128> {
+    if b == 0 {
+        return None;
+    }
+
+    Some(a / b + u128::from(a % b != 0))
+}
+
+/// Grosses a net input up by `fee` bps. Returns the gross input and the fee
+/// part of it, rounded up.
+pub fn gross_up(net_in: u64, fee: u16) -> Option<(u64, u64)> {
+    if fee >= 10_000 {
+        return None;
+    }
+
+    let gross_in = div_ceil((net_in as u128).checked_mul(10_000)?, 10_000 - fee as u128)?;
+    let gross_in = u64::try_from(gross_in).ok()?;
+
+    Some((gross_in, gross_in - net_in))
+}
@@ -0,0 +1,192 @@
+use crate::{
+    div_ceil, exact_in_amount_out, exact_out_amount_in, gross_up, isqrt, stable_amount_in,
+    stable_amount_out, stable_invariant,
+};
+
+pub const PRICE_PRECISION: u128 = 1_000_000;
+
+/// Invariant a pooled-liquidity swap is priced on. StableSwap pools carry the
+/// amplification in effect at the time of the quote.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Curve {
+    ConstantProduct,
+    StableSwap { amp: u64 },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapQuote {
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee: u64,
+    pub protocol_fee: u64,
+}
+
+impl SwapQuote {
+    /// Part of the input that ends up in the pool vault.
+    pub fn pool_amount_in(&self) -> u64 {
+        self.amount_in - self.protocol_fee
+    }
+
+    /// Realized price of the input in the output token, scaled by
+    /// `PRICE_PRECISION`.
+    pub fn price(&self) -> Option<u64> {
+        (self.amount_out as u128)
+            .checked_mul(PRICE_PRECISION)?
+            .checked_div(self.amount_in as u128)
+            .and_then(|p| u64::try_from(p).ok())
+    }
+
+    /// Pool reserves once this quote has been settled against `x`/`y`.
+    pub fn reserves_after(&self, x: u64, y: u64, is_x: bool) -> Option<(u64, u64)> {
+        match is_x {
+            true => x
+                .checked_add(self.pool_amount_in())
+                .zip(y.checked_sub(self.amount_out)),
+            false => x
+                .checked_sub(self.amount_out)
+                .zip(y.checked_add(self.pool_amount_in())),
+        }
+    }
+}
+
+/// Exact-input quote of `amount_in` against `reserve_in`/`reserve_out`. The
+/// LP `fee` and `protocol_fee`, both in bps, are taken from the input, rounded
+/// down, before it is priced on the curve. The protocol share is split out
+/// but still paid by the user.
+pub fn quote_swap(
+    curve: Curve,
+    reserve_in: u64,
+    reserve_out: u64,
+    amount_in: u64,
+    fee: u16,
+    protocol_fee: u16,
+) -> Option<SwapQuote> {
+    let total_fee = fee.checked_add(protocol_fee).filter(|f| *f <= 10_000)?;
+
+    let fee_amount = (amount_in as u128 * total_fee as u128 / 10_000) as u64;
+    let net_in = amount_in - fee_amount;
+
+    let amount_out = match curve {
+        Curve::ConstantProduct => exact_in_amount_out(reserve_in, reserve_out, net_in)?,
+        Curve::StableSwap { amp } => stable_amount_out(reserve_in, reserve_out, net_in, amp)?,
+    };
+
+    Some(SwapQuote {
+        amount_in,
+        amount_out,
+        fee: fee_amount,
+        protocol_fee: protocol_fee_share(fee_amount, fee, protocol_fee)?,
+    })
+}
+
+/// Exact-output quote for `amount_out` against `reserve_in`/`reserve_out`,
+/// with the input and its fee rounded up.
+pub fn quote_swap_exact_out(
+    curve: Curve,
+    reserve_in: u64,
+    reserve_out: u64,
+    amount_out: u64,
+    fee: u16,
+    protocol_fee: u16,
+) -> Option<SwapQuote> {
+    let total_fee = fee.checked_add(protocol_fee)?;
+
+    let (amount_in, fee_amount) = match curve {
+        Curve::ConstantProduct => {
+            exact_out_amount_in(reserve_in, reserve_out, amount_out, total_fee)?
+        }
+        Curve::StableSwap { amp } => gross_up(
+            stable_amount_in(reserve_in, reserve_out, amount_out, amp)?,
+            total_fee,
+        )?,
+    };
+
+    Some(SwapQuote {
+        amount_in,
+        amount_out,
+        fee: fee_amount,
+        protocol_fee: protocol_fee_share(fee_amount, fee, protocol_fee)?,
+    })
+}
+
+/// Portion of a swap fee owed to the protocol; the remainder stays in the
+/// vault for LPs. Rounds down in favour of LPs.
+pub fn protocol_fee_share(fee_amount: u64, fee: u16, protocol_fee: u16) -> Option<u64> {
+    let total_fee = fee as u128 + protocol_fee as u128;
+    if total_fee == 0 {
+        return Some(0);
+    }
+
+    u64::try_from((fee_amount as u128).checked_mul(protocol_fee as u128)? / total_fee).ok()
+}
+
+/// Liquidity minted for the first deposit of `x` and `y`, before any of it is
+/// locked away.
+pub fn initial_liquidity(curve: Curve, x: u64, y: u64) -> Option<u64> {
+    let liquidity = match curve {
+        Curve::ConstantProduct => isqrt(x as u128 * y as u128),
+        Curve::StableSwap { amp } => stable_invariant(x, y, amp)?,
+    };
+
+    u64::try_from(liquidity).ok()
+}
+
+/// X and Y owed for minting `lp` against reserves `x`/`y` and LP supply `l`.
+/// Rounds up so a deposit can never buy more than its share of the pool.
+pub fn quote_deposit(x: u64, y: u64, l: u64, lp: u64) -> Option<(u64, u64)> {
+    if l == 0 {
+        return None;
+    }
+
+    let dx = div_ceil((lp as u128).checked_mul(x as u128)?, l as u128)?;
+    let dy = div_ceil((lp as u128).checked_mul(y as u128)?, l as u128)?;
+
+    Some((u64::try_from(dx).ok()?, u64::try_from(dy).ok()?))
+}
+
+/// LP bought by depositing at most `max_x`/`max_y` into a pool holding
+/// `x`/`y` with `l` LP outstanding, limited by the scarcer side and rounded
+/// down. `quote_deposit` of the result never asks for more than the maxima.
+pub fn quote_deposit_lp(x: u64, y: u64, l: u64, max_x: u64, max_y: u64) -> Option<u64> {
+    if x == 0 || y == 0 || l == 0 {
+        return None;
+    }
+
+    let lp_x = (max_x as u128).checked_mul(l as u128)? / x as u128;
+    let lp_y = (max_y as u128).checked_mul(l as u128)? / y as u128;
+
+    u64::try_from(lp_x.min(lp_y)).ok()
+}
+
+/// X and Y paid out for burning `lp` against reserves `x`/`y` and LP supply
+/// `l`. Rounds down so a withdrawal can never take more than its share.
+/// Proportional withdrawals leave the price unchanged on either curve.
+pub fn quote_withdraw(x: u64, y: u64, l: u64, lp: u64) -> Option<(u64, u64)> {
+    if l == 0 || lp > l {
+        return None;
+    }
+
+    let dx = (lp as u128).checked_mul(x as u128)? / l as u128;
+    let dy = (lp as u128).checked_mul(y as u128)? / l as u128;
+
+    Some((dx as u64, dy as u64))
+}
+
+/// How far, in bps and rounded up, the realized price of trading `amount_in`
+/// for `amount_out` falls short of the reserve ratio `reserve_out /
+/// reserve_in`, fees included. The reserve ratio is the marginal price of
+/// constant-product pools; StableSwap pools price closer to 1:1, so their
+/// impact is floored at zero.
+pub fn price_impact(
+    reserve_in: u64,
+    reserve_out: u64,
+    amount_in: u64,
+    amount_out: u64,
+) -> Option<u64> {
+    let spot = (amount_in as u128).checked_mul(reserve_out as u128)?;
+    let realized = (amount_out as u128).checked_mul(reserve_in as u128)?;
+
+    let impact = div_ceil(spot.saturating_sub(realized).checked_mul(10_000)?, spot)?;
+
+    u64::try_from(impact).ok()
+}
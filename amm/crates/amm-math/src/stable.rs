@@ -0,0 +1,105 @@
+use crate::U256;
+
+/// Newton iterations allowed before a StableSwap computation gives up.
+const STABLE_ITERATIONS: usize = 64;
+
+/// StableSwap invariant `D` of a two-token pool with reserves `x`/`y` and
+/// amplification `amp`, i.e. the `D` solving
+/// `4A(x + y) + D = 4AD + D³ / 4xy`.
+pub fn stable_invariant(x: u64, y: u64, amp: u64) -> Option<u128> {
+    let (x, y) = (x as u128, y as u128);
+    let sum = x.checked_add(y)?;
+    if sum == 0 {
+        return Some(0);
+    }
+    if x == 0 || y == 0 {
+        return None;
+    }
+
+    let ann = (amp as u128).checked_mul(4)?;
+    let four_xy = U256::from(x) * U256::from(y) * U256::from(4);
+    let mut d = sum;
+    for _ in 0..STABLE_ITERATIONS {
+        // `D³ / 4xy` in one division: truncating the partial products sets
+        // Newton cycling between values further apart than the tolerance on
+        // lopsided pools.
+        let d_p = U256::from(d).checked_pow(U256::from(3))? / four_xy;
+        let d_p = u128::try_from(d_p).ok()?;
+
+        let previous = d;
+        let numerator = ann
+            .checked_mul(sum)?
+            .checked_add(d_p.checked_mul(2)?)?
+            .checked_mul(d)?;
+        let denominator = ann
+            .checked_sub(1)?
+            .checked_mul(d)?
+            .checked_add(d_p.checked_mul(3)?)?;
+        d = numerator.checked_div(denominator)?;
+
+        if d.abs_diff(previous) <= 1 {
+            return Some(d);
+        }
+    }
+
+    None
+}
+
+/// Balance of one side of a StableSwap pool with invariant `d` when the other
+/// side holds `x`. Rounded up, so quotes built on it favour the pool.
+pub fn stable_reserve(x: u64, d: u128, amp: u64) -> Option<u64> {
+    let x = x as u128;
+    if x == 0 {
+        return None;
+    }
+
+    let ann = (amp as u128).checked_mul(4)?;
+    let c = d
+        .checked_mul(d)?
+        .checked_div(x.checked_mul(2)?)?
+        .checked_mul(d)?
+        .checked_div(ann.checked_mul(2)?)?;
+    let b = x.checked_add(d.checked_div(ann)?)?;
+
+    let mut y = d;
+    for _ in 0..STABLE_ITERATIONS {
+        let previous = y;
+        y = y
+            .checked_mul(y)?
+            .checked_add(c)?
+            .checked_div(y.checked_mul(2)?.checked_add(b)?.checked_sub(d)?)?;
+
+        if y.abs_diff(previous) <= 1 {
+            return u64::try_from(y.checked_add(1)?).ok();
+        }
+    }
+
+    None
+}
+
+/// Output of a StableSwap pool holding `reserve_in`/`reserve_out` for a net
+/// (fee already removed) input of `net_in`. Rounded down.
+pub fn stable_amount_out(reserve_in: u64, reserve_out: u64, net_in: u64, amp: u64) -> Option<u64> {
+    let d = stable_invariant(reserve_in, reserve_out, amp)?;
+    let reserve_out_after = stable_reserve(reserve_in.checked_add(net_in)?, d, amp)?;
+
+    reserve_out.checked_sub(reserve_out_after)
+}
+
+/// Net (fee excluded) input a StableSwap pool holding `reserve_in`/
+/// `reserve_out` needs to pay out `amount_out`. Rounded up.
+pub fn stable_amount_in(
+    reserve_in: u64,
+    reserve_out: u64,
+    amount_out: u64,
+    amp: u64,
+) -> Option<u64> {
+    if amount_out >= reserve_out {
+        return None;
+    }
+
+    let d = stable_invariant(reserve_in, reserve_out, amp)?;
+    let reserve_in_after = stable_reserve(reserve_out - amount_out, d, amp)?;
+
+    reserve_in_after.checked_sub(reserve_in)
+}
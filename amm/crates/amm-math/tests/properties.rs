@@ -0,0 +1,227 @@
+use amm_math::{
+    decay_volatility, dynamic_fee, gross_up, initial_liquidity, price_impact, price_move_bps,
+    quote_deposit, quote_deposit_lp, quote_swap, quote_swap_exact_out, quote_withdraw,
+    stable_invariant, zap_swap_amount, Curve,
+};
+use proptest::prelude::*;
+
+const MAX_RESERVE: u64 = 1 << 48;
+
+fn curve() -> impl Strategy<Value = Curve> {
+    prop_oneof![
+        Just(Curve::ConstantProduct),
+        (1u64..=10_000).prop_map(|amp| Curve::StableSwap { amp }),
+    ]
+}
+
+fn fees() -> impl Strategy<Value = (u16, u16)> {
+    (0u16..=1_000, 0u16..=1_000)
+}
+
+/// Invariant of `curve` over `x`/`y`, comparable before and after a trade.
+fn invariant(curve: Curve, x: u64, y: u64) -> u128 {
+    match curve {
+        Curve::ConstantProduct => x as u128 * y as u128,
+        Curve::StableSwap { amp } => stable_invariant(x, y, amp).unwrap(),
+    }
+}
+
+proptest! {
+    #[test]
+    fn swap_never_decreases_invariant(
+        curve in curve(),
+        x in 1_000u64..MAX_RESERVE,
+        y in 1_000u64..MAX_RESERVE,
+        amount_in in 1u64..MAX_RESERVE,
+        (fee, protocol_fee) in fees(),
+        is_x: bool,
+    ) {
+        let (reserve_in, reserve_out) = match is_x {
+            true => (x, y),
+            false => (y, x),
+        };
+        let Some(quote) = quote_swap(curve, reserve_in, reserve_out, amount_in, fee, protocol_fee)
+        else {
+            return Ok(());
+        };
+
+        prop_assert!(quote.amount_out < reserve_out);
+        prop_assert!(quote.protocol_fee <= quote.fee);
+
+        let (x_after, y_after) = quote.reserves_after(x, y, is_x).unwrap();
+        prop_assert!(invariant(curve, x_after, y_after) >= invariant(curve, x, y));
+    }
+
+    #[test]
+    fn exact_out_input_buys_at_least_the_output(
+        curve in curve(),
+        x in 1_000u64..MAX_RESERVE,
+        y in 1_000u64..MAX_RESERVE,
+        share in 1u64..10_000,
+        (fee, protocol_fee) in fees(),
+    ) {
+        let amount_out = ((y as u128 * share as u128) / 10_000).max(1) as u64;
+        let Some(quote) = quote_swap_exact_out(curve, x, y, amount_out, fee, protocol_fee) else {
+            return Ok(());
+        };
+
+        prop_assert_eq!(quote.amount_out, amount_out);
+
+        // Paying the quoted input on the exact-in path must buy the output.
+        let exact_in = quote_swap(curve, x, y, quote.amount_in, fee, protocol_fee).unwrap();
+        prop_assert!(exact_in.amount_out >= amount_out);
+    }
+
+    #[test]
+    fn deposit_rounds_up_and_withdraw_rounds_down(
+        x in 1u64..MAX_RESERVE,
+        y in 1u64..MAX_RESERVE,
+        l in 1u64..MAX_RESERVE,
+        lp in 1u64..MAX_RESERVE,
+    ) {
+        let lp = lp.min(l);
+
+        let (dx, dy) = quote_deposit(x, y, l, lp).unwrap();
+        prop_assert!(dx as u128 * l as u128 >= lp as u128 * x as u128);
+        prop_assert!(dy as u128 * l as u128 >= lp as u128 * y as u128);
+
+        let (wx, wy) = quote_withdraw(x, y, l, lp).unwrap();
+        prop_assert!(wx as u128 * l as u128 <= lp as u128 * x as u128);
+        prop_assert!(wy as u128 * l as u128 <= lp as u128 * y as u128);
+
+        prop_assert!(wx <= dx && wy <= dy);
+    }
+
+    #[test]
+    fn deposit_lp_stays_within_the_maxima(
+        x in 1u64..MAX_RESERVE,
+        y in 1u64..MAX_RESERVE,
+        l in 1u64..MAX_RESERVE,
+        max_x in 0u64..MAX_RESERVE,
+        max_y in 0u64..MAX_RESERVE,
+    ) {
+        let lp = quote_deposit_lp(x, y, l, max_x, max_y).unwrap();
+        let (dx, dy) = quote_deposit(x, y, l, lp).unwrap();
+
+        prop_assert!(dx <= max_x && dy <= max_y);
+    }
+
+    #[test]
+    fn deposit_then_withdraw_never_profits(
+        x in 1u64..MAX_RESERVE,
+        y in 1u64..MAX_RESERVE,
+        l in 1u64..MAX_RESERVE,
+        lp in 1u64..MAX_RESERVE,
+    ) {
+        let (dx, dy) = quote_deposit(x, y, l, lp).unwrap();
+        let (wx, wy) = quote_withdraw(x + dx, y + dy, l + lp, lp).unwrap();
+
+        prop_assert!(wx <= dx && wy <= dy);
+    }
+
+    #[test]
+    fn withdraw_rejects_more_than_the_supply(
+        x: u64,
+        y: u64,
+        l in 1u64..u64::MAX,
+    ) {
+        prop_assert!(quote_withdraw(x, y, l, l + 1).is_none());
+    }
+
+    #[test]
+    fn initial_liquidity_is_bounded_by_reserves(
+        curve in curve(),
+        x in 1u64..MAX_RESERVE,
+        y in 1u64..MAX_RESERVE,
+    ) {
+        let liquidity = initial_liquidity(curve, x, y).unwrap();
+
+        // The geometric mean for constant product, at most the sum for
+        // StableSwap.
+        prop_assert!(liquidity as u128 <= x as u128 + y as u128);
+    }
+
+    #[test]
+    fn gross_up_covers_net_and_fee(net_in in 0u64..MAX_RESERVE, fee in 0u16..10_000) {
+        let (gross_in, fee_amount) = gross_up(net_in, fee).unwrap();
+
+        prop_assert_eq!(gross_in - fee_amount, net_in);
+        prop_assert!(fee_amount as u128 * 10_000 >= gross_in as u128 * fee as u128);
+    }
+
+    #[test]
+    fn constant_product_price_impact_is_bounded(
+        x in 1_000u64..MAX_RESERVE,
+        y in 1_000u64..MAX_RESERVE,
+        amount_in in 1u64..MAX_RESERVE,
+        fee in 0u16..1_000,
+    ) {
+        let quote = quote_swap(Curve::ConstantProduct, x, y, amount_in, fee, 0).unwrap();
+        let impact = price_impact(x, y, quote.amount_in, quote.amount_out).unwrap();
+
+        // Every constant-product trade fills below the reserve ratio.
+        prop_assert!((1..=10_000).contains(&impact));
+    }
+
+    #[test]
+    fn zap_swaps_at_most_the_deposit(
+        reserve_in in 1u64..=u64::MAX,
+        amount_in in 1u64..=u64::MAX,
+        fee in 0u16..1_000,
+    ) {
+        let swap_in = zap_swap_amount(reserve_in, amount_in, fee).unwrap();
+
+        prop_assert!(swap_in <= amount_in);
+        if fee == 0 {
+            // Without a fee the remainder is worth exactly the output, which
+            // takes at most half the deposit.
+            prop_assert!(swap_in <= amount_in / 2);
+        }
+    }
+
+    #[test]
+    fn dynamic_fee_stays_within_bounds(
+        min_fee in 0u16..=1_000,
+        spread in 0u16..=1_000,
+        volatility: u32,
+        elapsed in 0i64..10_000,
+    ) {
+        let max_fee = min_fee + spread;
+        let decayed = decay_volatility(volatility, elapsed, 600);
+        prop_assert!(decayed <= volatility);
+
+        let fee = dynamic_fee(min_fee, max_fee, decayed);
+        prop_assert!((min_fee..=max_fee).contains(&fee));
+        prop_assert!(fee >= dynamic_fee(min_fee, max_fee, decay_volatility(decayed, 60, 600)));
+    }
+
+    #[test]
+    fn swaps_register_price_moves(
+        x in 1_000u64..MAX_RESERVE,
+        y in 1_000u64..MAX_RESERVE,
+        amount_in in 1u64..MAX_RESERVE,
+    ) {
+        prop_assert_eq!(price_move_bps(x, y, x, y), Some(0));
+
+        let quote = quote_swap(Curve::ConstantProduct, x, y, amount_in, 0, 0).unwrap();
+        let (x_after, y_after) = quote.reserves_after(x, y, true).unwrap();
+        if quote.amount_out > 0 {
+            prop_assert!(price_move_bps(x, y, x_after, y_after).unwrap() > 0);
+        }
+    }
+}
+
+#[test]
+fn zap_swap_amount_handles_large_reserves() {
+    // Past 9.2e14 of `reserve_in` the discriminant no longer fits in u128.
+    let reserve_in = 1_000_000_000_000_000_000;
+    let amount_in = 1_000_000_000_000_000;
+    let swap_in = zap_swap_amount(reserve_in, amount_in, 30).unwrap();
+
+    // A deposit this small next to the reserves is swapped nearly in half,
+    // a little more to cover the fee.
+    assert!(swap_in > amount_in / 2 && swap_in < amount_in / 2 + amount_in / 1_000);
+
+    let swap_in = zap_swap_amount(u64::MAX, u64::MAX, 0).unwrap();
+    assert!(swap_in <= u64::MAX / 2);
+}
@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+
+use crate::{AmmError, AuthorityAcceptedEvent, Config};
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    pub pending_authority: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = config.pending_authority == Some(pending_authority.key()) @ AmmError::InvalidAuthority,
+        seeds = [
+            b"config",
+            config.seed.to_le_bytes().as_ref(),
+            config.mint_x.as_ref(),
+            config.mint_y.as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+impl<'info> AcceptAuthority<'info> {
+    pub fn accept_authority(&mut self) -> Result<()> {
+        emit!(AuthorityAcceptedEvent {
+            pool: self.config.key(),
+            old_authority: self.config.authority,
+            new_authority: self.pending_authority.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        self.config.authority = self.pending_authority.key();
+        self.config.pending_authority = None;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,216 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    AmmError, AmpRampEvent, AuthorityProposedEvent, Config, CurveType, DynamicFeeEvent,
+    FeeUpdatedEvent, FlashLoanFeeUpdatedEvent, LockEvent, MaxTradeUpdatedEvent, MAX_AMP_CHANGE,
+    MIN_RAMP_DURATION,
+};
+
+#[derive(Accounts)]
+pub struct Admin<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [
+            b"config",
+            config.seed.to_le_bytes().as_ref(),
+            config.mint_x.as_ref(),
+            config.mint_y.as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+impl<'info> Admin<'info> {
+    pub fn lock(&mut self) -> Result<()> {
+        self.set_locked(true)
+    }
+
+    pub fn unlock(&mut self) -> Result<()> {
+        require!(!self.config.closing, AmmError::PoolFrozen);
+        self.set_locked(false)
+    }
+
+    fn set_locked(&mut self, locked: bool) -> Result<()> {
+        self.config.locked = locked;
+
+        emit!(LockEvent {
+            pool: self.config.key(),
+            authority: self.authority.key(),
+            locked,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    pub fn update_fee(&mut self, fee: u16, protocol_fee: u16) -> Result<()> {
+        Config::check_fees(fee, protocol_fee)?;
+        if self.config.dynamic_fee {
+            Config::check_dynamic_fee(self.config.min_fee, self.config.max_fee, protocol_fee)?;
+        }
+
+        emit!(FeeUpdatedEvent {
+            pool: self.config.key(),
+            authority: self.authority.key(),
+            old_fee: self.config.fee,
+            new_fee: fee,
+            old_protocol_fee: self.config.protocol_fee,
+            new_protocol_fee: protocol_fee,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        self.config.fee = fee;
+        self.config.protocol_fee = protocol_fee;
+
+        Ok(())
+    }
+
+    /// Switches the pool between its static `fee` and a dynamic LP fee that
+    /// rises from `min_fee` towards `max_fee` bps with recent price movement.
+    /// Concentrated pools only support the static fee.
+    pub fn set_dynamic_fee(&mut self, enabled: bool, min_fee: u16, max_fee: u16) -> Result<()> {
+        require!(
+            self.config.curve != CurveType::Concentrated,
+            AmmError::InvalidCurve
+        );
+        if enabled {
+            Config::check_dynamic_fee(min_fee, max_fee, self.config.protocol_fee)?;
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+
+        self.config.dynamic_fee = enabled;
+        self.config.min_fee = min_fee;
+        self.config.max_fee = max_fee;
+        self.config.volatility = 0;
+        self.config.volatility_updated_ts = now;
+
+        emit!(DynamicFeeEvent {
+            pool: self.config.key(),
+            authority: self.authority.key(),
+            enabled,
+            min_fee,
+            max_fee,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Caps every swap at `max_trade_bps` of the pool's reserves; zero
+    /// removes the cap.
+    pub fn update_max_trade(&mut self, max_trade_bps: u16) -> Result<()> {
+        Config::check_max_trade(max_trade_bps)?;
+
+        emit!(MaxTradeUpdatedEvent {
+            pool: self.config.key(),
+            authority: self.authority.key(),
+            old_max_trade_bps: self.config.max_trade_bps,
+            new_max_trade_bps: max_trade_bps,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        self.config.max_trade_bps = max_trade_bps;
+
+        Ok(())
+    }
+
+    pub fn update_flash_loan_fee(&mut self, flash_loan_fee: u16) -> Result<()> {
+        Config::check_flash_loan_fee(flash_loan_fee)?;
+
+        emit!(FlashLoanFeeUpdatedEvent {
+            pool: self.config.key(),
+            authority: self.authority.key(),
+            old_flash_loan_fee: self.config.flash_loan_fee,
+            new_flash_loan_fee: flash_loan_fee,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        self.config.flash_loan_fee = flash_loan_fee;
+
+        Ok(())
+    }
+
+    pub fn propose_authority(&mut self, new_authority: Pubkey) -> Result<()> {
+        self.config.pending_authority = Some(new_authority);
+
+        emit!(AuthorityProposedEvent {
+            pool: self.config.key(),
+            authority: self.authority.key(),
+            pending_authority: new_authority,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Starts moving the StableSwap amplification from its current value to
+    /// `target_amp`, linearly until `stop_ts`. Replaces any ramp in progress.
+    pub fn ramp_amp(&mut self, target_amp: u64, stop_ts: i64) -> Result<()> {
+        require!(
+            self.config.curve == CurveType::StableSwap,
+            AmmError::InvalidCurve
+        );
+        Config::check_amp(target_amp)?;
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            stop_ts >= now.saturating_add(MIN_RAMP_DURATION),
+            AmmError::InvalidAmp
+        );
+
+        let current_amp = self.config.amp(now);
+        require!(
+            target_amp <= current_amp.saturating_mul(MAX_AMP_CHANGE)
+                && current_amp <= target_amp.saturating_mul(MAX_AMP_CHANGE),
+            AmmError::InvalidAmp
+        );
+
+        self.config.initial_amp = current_amp;
+        self.config.target_amp = target_amp;
+        self.config.ramp_start_ts = now;
+        self.config.ramp_stop_ts = stop_ts;
+
+        emit!(AmpRampEvent {
+            pool: self.config.key(),
+            authority: self.authority.key(),
+            initial_amp: current_amp,
+            target_amp,
+            ramp_start_ts: now,
+            ramp_stop_ts: stop_ts,
+        });
+
+        Ok(())
+    }
+
+    /// Freezes the amplification at its current value.
+    pub fn stop_ramp_amp(&mut self) -> Result<()> {
+        require!(
+            self.config.curve == CurveType::StableSwap,
+            AmmError::InvalidCurve
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let current_amp = self.config.amp(now);
+
+        self.config.initial_amp = current_amp;
+        self.config.target_amp = current_amp;
+        self.config.ramp_start_ts = now;
+        self.config.ramp_stop_ts = now;
+
+        emit!(AmpRampEvent {
+            pool: self.config.key(),
+            authority: self.authority.key(),
+            initial_amp: current_amp,
+            target_amp: current_amp,
+            ramp_start_ts: now,
+            ramp_stop_ts: now,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,88 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::{Farm, RewardsClaimedEvent, Stake};
+
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = reward_mint,
+        seeds = [b"farm", farm.config.as_ref()],
+        bump = farm.bump,
+    )]
+    pub farm: Account<'info, Farm>,
+
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        has_one = owner,
+        has_one = farm,
+        seeds = [b"stake", farm.key().as_ref(), owner.key().as_ref()],
+        bump = stake.bump,
+    )]
+    pub stake: Account<'info, Stake>,
+
+    #[account(
+        mut,
+        seeds = [b"farm_rewards", farm.key().as_ref()],
+        bump = farm.reward_vault_bump,
+    )]
+    pub reward_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = reward_mint,
+        associated_token::authority = owner,
+    )]
+    pub user_reward_ata: InterfaceAccount<'info, TokenAccount>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ClaimRewards<'info> {
+    pub fn claim(&mut self) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        self.farm.update(now)?;
+        self.stake.accrue_rewards(self.farm.reward_per_share)?;
+
+        let amount = self.stake.rewards_owed;
+        if amount == 0 {
+            return Ok(());
+        }
+        self.stake.rewards_owed = 0;
+
+        let seeds = &[&b"farm"[..], self.farm.config.as_ref(), &[self.farm.bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_accounts = TransferChecked {
+            from: self.reward_vault.to_account_info(),
+            to: self.user_reward_ata.to_account_info(),
+            authority: self.farm.to_account_info(),
+            mint: self.reward_mint.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        transfer_checked(cpi_ctx, amount, self.reward_mint.decimals)?;
+
+        emit!(RewardsClaimedEvent {
+            farm: self.farm.key(),
+            owner: self.owner.key(),
+            amount,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,348 @@
+use amm_math::quote_withdraw;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    burn, close_account, transfer_checked, Burn, CloseAccount, Mint, TokenAccount, TokenInterface,
+    TransferChecked,
+};
+
+use crate::{AmmError, Config, CurveType, Farm, Oracle, PoolClosedEvent};
+
+/// Accounts each LP holder paid out by `close_pool` passes through
+/// `remaining_accounts`, in order: their LP token account, then the X and Y
+/// token accounts of the same owner to receive their share. All but the LP
+/// account must be writable.
+pub const CLOSE_HOLDER_ACCOUNTS: usize = 3;
+
+#[derive(Accounts)]
+pub struct ClosePool<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        close = authority,
+        has_one = authority,
+        has_one = mint_x,
+        has_one = mint_y,
+        constraint = config.curve != CurveType::Concentrated @ AmmError::InvalidCurve,
+        constraint = config.closing @ AmmError::PoolNotFrozen,
+        seeds = [
+            b"config",
+            config.seed.to_le_bytes().as_ref(),
+            mint_x.key().as_ref(),
+            mint_y.key().as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"lp", config.key().as_ref()],
+        bump = config.lp_mint_bump,
+    )]
+    pub mint_lp: InterfaceAccount<'info, Mint>,
+
+    pub mint_x: InterfaceAccount<'info, Mint>,
+    pub mint_y: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [b"dead", config.key().as_ref()],
+        bump,
+    )]
+    /// CHECK: only signs to burn the locked LP of a pool being closed
+    pub dead: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"lp_lock", config.key().as_ref()],
+        bump = config.lp_lock_bump,
+    )]
+    pub lp_lock: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = config.vault_x,
+        token::mint = mint_x,
+        token::authority = config,
+    )]
+    pub vault_x: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = config.vault_y,
+        token::mint = mint_y,
+        token::authority = config,
+    )]
+    pub vault_y: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"fee_x", config.key().as_ref()],
+        bump = config.fee_vault_x_bump,
+    )]
+    pub fee_vault_x: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"fee_y", config.key().as_ref()],
+        bump = config.fee_vault_y_bump,
+    )]
+    pub fee_vault_y: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = mint_x,
+    )]
+    pub treasury_x: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = mint_y,
+    )]
+    pub treasury_y: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"oracle", config.key().as_ref()],
+        bump = config.oracle_bump,
+    )]
+    pub oracle: Account<'info, Oracle>,
+
+    #[account(
+        seeds = [b"farm", config.key().as_ref()],
+        bump,
+    )]
+    /// CHECK: the pool's farm, if one was opened; it must have nothing staked
+    pub farm: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> ClosePool<'info> {
+    /// Decommissions a pool frozen by `freeze_pool` and returns the rent of
+    /// its accounts to the authority.
+    ///
+    /// Holders are expected to have withdrawn since the freeze. LP still
+    /// outstanding is settled by a forced final distribution: every remaining
+    /// holder must be listed in `remaining_accounts` and is paid their
+    /// pro-rata share of the reserves, as `withdraw` would. Their LP is left
+    /// worthless. What remains, the share of the locked `MINIMUM_LIQUIDITY`
+    /// and rounding dust, goes to the treasury with the protocol fees. With
+    /// no LP outstanding nothing needs to be listed. A holder too scattered
+    /// to list only delays reclaiming the rent: the pool is already delisted
+    /// and takes no trades.
+    ///
+    /// A farm on the pool must have nothing staked: its stakers could no
+    /// longer unstake once the config is gone. Rewards stay claimable.
+    ///
+    /// Not everything can be cleaned up. Holders' LP cannot be burned without
+    /// their signatures, and the LP mint has no close authority, so both
+    /// stay behind. Concentrated pools have positions instead of LP and are
+    /// not supported. Vaults of Token-2022 mints with transfer fees must have
+    /// their withheld fees harvested to the mint before they can be closed.
+    pub fn close_pool(
+        &mut self,
+        remaining_accounts: &'info [AccountInfo<'info>],
+        dead_bump: u8,
+    ) -> Result<()> {
+        require!(
+            remaining_accounts.len() % CLOSE_HOLDER_ACCOUNTS == 0,
+            AmmError::InvalidAmount
+        );
+        if !self.farm.data_is_empty() {
+            let farm = Farm::try_deserialize(&mut &self.farm.try_borrow_data()?[..])?;
+            require!(farm.total_staked == 0, AmmError::FarmNotEmpty);
+        }
+
+        let reserve_x = self.vault_x.amount;
+        let reserve_y = self.vault_y.amount;
+        let supply = self.mint_lp.supply;
+        let outstanding = supply - self.lp_lock.amount;
+
+        let mut listed = 0u64;
+        let mut paid_x = 0u64;
+        let mut paid_y = 0u64;
+        let mut holders: Vec<Pubkey> = Vec::new();
+
+        for accounts in remaining_accounts.chunks(CLOSE_HOLDER_ACCOUNTS) {
+            let lp = InterfaceAccount::<TokenAccount>::try_from(&accounts[0])?;
+            let to_x = InterfaceAccount::<TokenAccount>::try_from(&accounts[1])?;
+            let to_y = InterfaceAccount::<TokenAccount>::try_from(&accounts[2])?;
+
+            require!(
+                lp.mint == self.mint_lp.key()
+                    && lp.key() != self.lp_lock.key()
+                    && !holders.contains(&lp.key())
+                    && to_x.mint == self.mint_x.key()
+                    && to_y.mint == self.mint_y.key()
+                    && to_x.owner == lp.owner
+                    && to_y.owner == lp.owner,
+                AmmError::HolderMismatch
+            );
+            holders.push(lp.key());
+
+            let (amount_x, amount_y) = quote_withdraw(reserve_x, reserve_y, supply, lp.amount)
+                .ok_or(AmmError::InvalidAmount)?;
+
+            self.withdraw_tokens(to_x.to_account_info(), amount_x, true)?;
+            self.withdraw_tokens(to_y.to_account_info(), amount_y, false)?;
+
+            listed += lp.amount;
+            paid_x += amount_x;
+            paid_y += amount_y;
+        }
+
+        require!(listed == outstanding, AmmError::PoolNotEmpty);
+
+        // Sweep the remains of the reserves and the protocol fees.
+        self.sweep_tokens(self.vault_x.to_account_info(), reserve_x - paid_x, true)?;
+        self.sweep_tokens(self.vault_y.to_account_info(), reserve_y - paid_y, false)?;
+        self.sweep_tokens(
+            self.fee_vault_x.to_account_info(),
+            self.fee_vault_x.amount,
+            true,
+        )?;
+        self.sweep_tokens(
+            self.fee_vault_y.to_account_info(),
+            self.fee_vault_y.amount,
+            false,
+        )?;
+
+        self.burn_locked_lp(dead_bump)?;
+
+        self.close_vault(self.vault_x.to_account_info())?;
+        self.close_vault(self.vault_y.to_account_info())?;
+        self.close_vault(self.fee_vault_x.to_account_info())?;
+        self.close_vault(self.fee_vault_y.to_account_info())?;
+
+        emit!(PoolClosedEvent {
+            pool: self.config.key(),
+            authority: self.authority.key(),
+            lp_settled: outstanding,
+            amount_x: paid_x,
+            amount_y: paid_y,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Burns the locked LP and closes its account, signed by the `dead` PDA
+    /// that otherwise never signs.
+    fn burn_locked_lp(&self, dead_bump: u8) -> Result<()> {
+        let config = self.config.key();
+        let seeds = &[&b"dead"[..], config.as_ref(), &[dead_bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        if self.lp_lock.amount > 0 {
+            let cpi_accounts = Burn {
+                mint: self.mint_lp.to_account_info(),
+                from: self.lp_lock.to_account_info(),
+                authority: self.dead.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                cpi_accounts,
+                signer_seeds,
+            );
+            burn(cpi_ctx, self.lp_lock.amount)?;
+        }
+
+        let cpi_accounts = CloseAccount {
+            account: self.lp_lock.to_account_info(),
+            destination: self.authority.to_account_info(),
+            authority: self.dead.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            cpi_accounts,
+            signer_seeds,
+        );
+
+        close_account(cpi_ctx)
+    }
+
+    fn sweep_tokens(&self, from: AccountInfo<'info>, amount: u64, is_x: bool) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let to = match is_x {
+            true => self.treasury_x.to_account_info(),
+            false => self.treasury_y.to_account_info(),
+        };
+
+        self.transfer_tokens(from, to, amount, is_x)
+    }
+
+    fn withdraw_tokens(&self, to: AccountInfo<'info>, amount: u64, is_x: bool) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let from = match is_x {
+            true => self.vault_x.to_account_info(),
+            false => self.vault_y.to_account_info(),
+        };
+
+        self.transfer_tokens(from, to, amount, is_x)
+    }
+
+    fn transfer_tokens(
+        &self,
+        from: AccountInfo<'info>,
+        to: AccountInfo<'info>,
+        amount: u64,
+        is_x: bool,
+    ) -> Result<()> {
+        let (mint, decimals) = match is_x {
+            true => (self.mint_x.to_account_info(), self.mint_x.decimals),
+            false => (self.mint_y.to_account_info(), self.mint_y.decimals),
+        };
+
+        let seed = self.config.seed.to_le_bytes();
+        let seeds = &[
+            &b"config"[..],
+            seed.as_ref(),
+            self.config.mint_x.as_ref(),
+            self.config.mint_y.as_ref(),
+            &[self.config.config_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_accounts = TransferChecked {
+            from,
+            to,
+            authority: self.config.to_account_info(),
+            mint,
+        };
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        transfer_checked(cpi_ctx, amount, decimals)
+    }
+
+    fn close_vault(&self, vault: AccountInfo<'info>) -> Result<()> {
+        let seed = self.config.seed.to_le_bytes();
+        let seeds = &[
+            &b"config"[..],
+            seed.as_ref(),
+            self.config.mint_x.as_ref(),
+            self.config.mint_y.as_ref(),
+            &[self.config.config_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_accounts = CloseAccount {
+            account: vault,
+            destination: self.authority.to_account_info(),
+            authority: self.config.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        close_account(cpi_ctx)
+    }
+}
@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+
+use crate::{AmmError, Config, Position};
+
+#[derive(Accounts)]
+pub struct ClosePosition<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [
+            b"config",
+            config.seed.to_le_bytes().as_ref(),
+            config.mint_x.as_ref(),
+            config.mint_y.as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        close = owner,
+        has_one = owner,
+        has_one = config,
+        constraint = position.liquidity == 0
+            && position.fees_owed_x == 0
+            && position.fees_owed_y == 0 @ AmmError::PositionNotEmpty,
+        seeds = [
+            b"position",
+            config.key().as_ref(),
+            owner.key().as_ref(),
+            position.tick_lower.to_le_bytes().as_ref(),
+            position.tick_upper.to_le_bytes().as_ref()
+        ],
+        bump = position.bump,
+    )]
+    pub position: Account<'info, Position>,
+}
@@ -0,0 +1,118 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+use crate::{Config, ProtocolFeesCollectedEvent};
+
+#[derive(Accounts)]
+pub struct CollectProtocolFees<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        has_one = authority,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [
+            b"config",
+            config.seed.to_le_bytes().as_ref(),
+            mint_x.key().as_ref(),
+            mint_y.key().as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    pub mint_x: InterfaceAccount<'info, Mint>,
+    pub mint_y: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"fee_x", config.key().as_ref()],
+        bump = config.fee_vault_x_bump,
+    )]
+    fee_vault_x: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"fee_y", config.key().as_ref()],
+        bump = config.fee_vault_y_bump,
+    )]
+    fee_vault_y: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = mint_x,
+    )]
+    treasury_x: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = mint_y,
+    )]
+    treasury_y: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> CollectProtocolFees<'info> {
+    pub fn collect_protocol_fees(&self) -> Result<()> {
+        let (amount_x, amount_y) = (self.fee_vault_x.amount, self.fee_vault_y.amount);
+
+        if amount_x > 0 {
+            self.withdraw_fees(amount_x, true)?;
+        }
+
+        if amount_y > 0 {
+            self.withdraw_fees(amount_y, false)?;
+        }
+
+        emit!(ProtocolFeesCollectedEvent {
+            pool: self.config.key(),
+            authority: self.authority.key(),
+            amount_x,
+            amount_y,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    fn withdraw_fees(&self, amount: u64, is_x: bool) -> Result<()> {
+        let (from, to, mint, decimals) = match is_x {
+            true => (
+                self.fee_vault_x.to_account_info(),
+                self.treasury_x.to_account_info(),
+                self.mint_x.to_account_info(),
+                self.mint_x.decimals,
+            ),
+            false => (
+                self.fee_vault_y.to_account_info(),
+                self.treasury_y.to_account_info(),
+                self.mint_y.to_account_info(),
+                self.mint_y.decimals,
+            ),
+        };
+
+        let seed = self.config.seed.to_le_bytes();
+        let seeds = &[
+            &b"config"[..],
+            seed.as_ref(),
+            self.config.mint_x.as_ref(),
+            self.config.mint_y.as_ref(),
+            &[self.config.config_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_accounts = TransferChecked {
+            from,
+            to,
+            authority: self.config.to_account_info(),
+            mint,
+        };
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        transfer_checked(cpi_ctx, amount, decimals)
+    }
+}
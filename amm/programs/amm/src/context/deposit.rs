@@ -1,3 +1,4 @@
+use amm_math::quote_deposit;
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
@@ -6,9 +7,11 @@ use anchor_spl::{
     },
 };
 
-use crate::{AmmError, Config};
-
-use constant_product_curve::ConstantProduct;
+use crate::{
+    check_deadline,
+    token::{gross_amount, net_amount},
+    AmmError, Config, DepositEvent, Oracle, MINIMUM_LIQUIDITY,
+};
 
 #[derive(Accounts)]
 pub struct Deposit<'info> {
@@ -61,6 +64,7 @@ pub struct Deposit<'info> {
 
     #[account(
         mut,
+        address = config.vault_x,
         token::mint = mint_x,
         token::authority = config,
     )]
@@ -68,49 +72,123 @@ pub struct Deposit<'info> {
 
     #[account(
         mut,
+        address = config.vault_y,
         token::mint = mint_y,
         token::authority = config,
     )]
     vault_y: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
-        seeds = [b"auth"],
-        bump,
+        mut,
+        seeds = [b"lp_lock", config.key().as_ref()],
+        bump = config.lp_lock_bump,
+    )]
+    lp_lock: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        seeds = [b"auth", config.key().as_ref()],
+        bump = config.auth_bump,
     )]
     /// CHECK: safe to use unchecked account here
     pub auth: UncheckedAccount<'info>,
 
+    #[account(
+        mut,
+        seeds = [b"oracle", config.key().as_ref()],
+        bump = config.oracle_bump,
+    )]
+    oracle: Account<'info, Oracle>,
+
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
 impl<'info> Deposit<'info> {
-    pub fn deposit(&self, amount: u64, max_x: u64, max_y: u64, expiration: i64) -> Result<()> {
-        require!(self.config.locked == true, AmmError::Locked);
-        require!(expiration > Clock::get()?.unix_timestamp, AmmError::Expired);
+    pub fn deposit(
+        &mut self,
+        amount: u64,
+        max_x: u64,
+        max_y: u64,
+        expiration: i64,
+        max_slot: Option<u64>,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+
+        self.config.check_unlocked()?;
+        check_deadline(&clock, expiration, max_slot)?;
         require!(
             amount > 0 && max_x > 0 && max_y > 0,
             AmmError::InvalidAmount
         );
 
-        let (x, y) = match self.mint_lp.supply == 0 && self.vault_x.amount == 0 {
-            true => (max_x, max_y),
+        self.oracle.update(
+            self.vault_x.amount,
+            self.vault_y.amount,
+            clock.unix_timestamp,
+        );
+
+        let is_first_deposit = self.mint_lp.supply == 0;
+
+        // On the first deposit `amount` is the minimum LP the caller accepts;
+        // afterwards it is the exact LP amount to mint. `x` and `y` are what
+        // reaches the vaults, net of transfer fees.
+        let (x, y, lp) = match is_first_deposit {
+            true => {
+                let x = net_amount(&self.mint_x, max_x)?;
+                let y = net_amount(&self.mint_y, max_y)?;
+                let lp = self
+                    .config
+                    .initial_liquidity(x, y)?
+                    .checked_sub(MINIMUM_LIQUIDITY)
+                    .ok_or(AmmError::InvalidDeposit)?;
+
+                require!(lp > 0, AmmError::InvalidDeposit);
+                require!(lp >= amount, AmmError::SlippageExceeded);
+
+                (x, y, lp)
+            }
             false => {
-                let amounts = ConstantProduct::xy_deposit_amounts_from_l(
+                let (x, y) = quote_deposit(
                     self.vault_x.amount,
                     self.vault_y.amount,
                     self.mint_lp.supply,
                     amount,
-                    6,
                 )
-                .map_err(|_| AmmError::InvalidDeposit)?;
+                .ok_or(AmmError::InvalidDeposit)?;
 
-                (amounts.x, amounts.y)
+                require!(x > 0 && y > 0, AmmError::InvalidDeposit);
+
+                (x, y, amount)
             }
         };
 
-        require!(x <= max_x && y <= max_y, AmmError::SlippageExceeded);
+        let minted = match is_first_deposit {
+            true => lp + MINIMUM_LIQUIDITY,
+            false => lp,
+        };
+        let lp_supply = self
+            .mint_lp
+            .supply
+            .checked_add(minted)
+            .ok_or(AmmError::InvalidDeposit)?;
+        let reserve_x = self
+            .vault_x
+            .amount
+            .checked_add(x)
+            .ok_or(AmmError::InvalidDeposit)?;
+        let reserve_y = self
+            .vault_y
+            .amount
+            .checked_add(y)
+            .ok_or(AmmError::InvalidDeposit)?;
+
+        let gross_x = gross_amount(&self.mint_x, x)?;
+        let gross_y = gross_amount(&self.mint_y, y)?;
+        require!(
+            gross_x <= max_x && gross_y <= max_y,
+            AmmError::SlippageExceeded
+        );
 
         let cpi_program = self.token_program.to_account_info();
         let cpi_accounts = TransferChecked {
@@ -122,7 +200,7 @@ impl<'info> Deposit<'info> {
 
         let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
 
-        transfer_checked(cpi_ctx, x, self.mint_x.decimals)?;
+        transfer_checked(cpi_ctx, gross_x, self.mint_x.decimals)?;
 
         let cpi_program = self.token_program.to_account_info();
         let cpi_accounts = TransferChecked {
@@ -133,21 +211,42 @@ impl<'info> Deposit<'info> {
         };
         let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
 
-        transfer_checked(cpi_ctx, x, self.mint_y.decimals)?;
+        transfer_checked(cpi_ctx, gross_y, self.mint_y.decimals)?;
+
+        if is_first_deposit {
+            self.mint_lp_tokens(self.lp_lock.to_account_info(), MINIMUM_LIQUIDITY)?;
+        }
+
+        self.mint_lp_tokens(self.user_lp_ata.to_account_info(), lp)?;
 
-        let seeds = &[&b"auth"[..], &[self.config.auth_bump]];
+        emit!(DepositEvent {
+            pool: self.config.key(),
+            user: self.user.key(),
+            amount_x: x,
+            amount_y: y,
+            lp_minted: lp,
+            reserve_x,
+            reserve_y,
+            lp_supply,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    fn mint_lp_tokens(&self, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        let config_key = self.config.key();
+        let seeds = &[&b"auth"[..], config_key.as_ref(), &[self.config.auth_bump]];
         let signer_seeds = &[&seeds[..]];
 
         let cpi_program = self.token_program.to_account_info();
         let cpi_accounts = MintTo {
             mint: self.mint_lp.to_account_info(),
-            to: self.user_lp_ata.to_account_info(),
+            to,
             authority: self.auth.to_account_info(),
         };
         let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
 
-        mint_to(cpi_ctx, amount)?;
-
-        Ok(())
+        mint_to(cpi_ctx, amount)
     }
 }
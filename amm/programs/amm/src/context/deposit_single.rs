@@ -0,0 +1,364 @@
+use amm_math::{quote_deposit, quote_deposit_lp, zap_swap_amount};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        mint_to, transfer_checked, Mint, MintTo, TokenAccount, TokenInterface, TransferChecked,
+    },
+};
+
+use crate::{check_deadline, token::net_amount, AmmError, Config, DepositEvent, Oracle, SwapEvent};
+
+#[derive(Accounts)]
+pub struct DepositSingle<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"config",
+            config.seed.to_le_bytes().as_ref(),
+            mint_x.key().as_ref(),
+            mint_y.key().as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"lp", config.key().as_ref()],
+        bump = config.lp_mint_bump,
+    )]
+    pub mint_lp: InterfaceAccount<'info, Mint>,
+
+    pub mint_x: InterfaceAccount<'info, Mint>,
+    pub mint_y: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_x,
+        associated_token::authority = user,
+    )]
+    user_x_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_y,
+        associated_token::authority = user,
+    )]
+    user_y_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_lp,
+        associated_token::authority = user,
+    )]
+    user_lp_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = config.vault_x,
+        token::mint = mint_x,
+        token::authority = config,
+    )]
+    vault_x: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = config.vault_y,
+        token::mint = mint_y,
+        token::authority = config,
+    )]
+    vault_y: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"fee_x", config.key().as_ref()],
+        bump = config.fee_vault_x_bump,
+    )]
+    fee_vault_x: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"fee_y", config.key().as_ref()],
+        bump = config.fee_vault_y_bump,
+    )]
+    fee_vault_y: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        seeds = [b"auth", config.key().as_ref()],
+        bump = config.auth_bump,
+    )]
+    /// CHECK: safe to use unchecked account here
+    pub auth: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"oracle", config.key().as_ref()],
+        bump = config.oracle_bump,
+    )]
+    oracle: Account<'info, Oracle>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> DepositSingle<'info> {
+    /// Deposits `amount_in` of one side of the pool. Part of it is swapped
+    /// through the pool's own curve so that the rest, together with the swap
+    /// output, can be added as balanced liquidity.
+    pub fn deposit_single(
+        &mut self,
+        amount_in: u64,
+        min_lp: u64,
+        expiration: i64,
+        max_slot: Option<u64>,
+        is_x: bool,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+
+        self.config.check_unlocked()?;
+        check_deadline(&clock, expiration, max_slot)?;
+        require!(amount_in > 0, AmmError::InvalidAmount);
+        require!(self.mint_lp.supply > 0, AmmError::InvalidDeposit);
+
+        self.oracle.update(
+            self.vault_x.amount,
+            self.vault_y.amount,
+            clock.unix_timestamp,
+        );
+
+        let (reserve_in, mint_in) = match is_x {
+            true => (self.vault_x.amount, &self.mint_x),
+            false => (self.vault_y.amount, &self.mint_y),
+        };
+        // Exactly `amount_in` is taken from the user; the pool works with
+        // what arrives after any transfer fee.
+        let net_in = net_amount(mint_in, amount_in)?;
+
+        // The split is exact for constant-product pools. On a StableSwap pool
+        // it is an approximation; any excess output is refunded below.
+        let swap_in = zap_swap_amount(reserve_in, net_in, self.config.total_fee()?)
+            .ok_or(AmmError::InvalidDeposit)?;
+        require!(swap_in > 0 && swap_in < net_in, AmmError::InvalidAmount);
+
+        let quote =
+            self.config
+                .quote_swap(self.vault_x.amount, self.vault_y.amount, swap_in, 1, is_x)?;
+
+        // The balanced leg is priced on the reserves the swap leaves. The swap
+        // mints no LP, so the supply it leaves is the current one.
+        let (x, y) = quote
+            .reserves_after(self.vault_x.amount, self.vault_y.amount, is_x)
+            .ok_or(AmmError::IncorrectK)?;
+        let supply = self.mint_lp.supply;
+
+        // The swap leg moves the price like any other swap.
+        self.config.record_price_move(
+            self.vault_x.amount,
+            self.vault_y.amount,
+            x,
+            y,
+            clock.unix_timestamp,
+        )?;
+
+        let remaining_in = net_in
+            .checked_sub(quote.amount_in)
+            .ok_or(AmmError::InvalidDeposit)?;
+        let (max_x, max_y) = match is_x {
+            true => (remaining_in, quote.amount_out),
+            false => (quote.amount_out, remaining_in),
+        };
+
+        let lp = quote_deposit_lp(x, y, supply, max_x, max_y).ok_or(AmmError::InvalidDeposit)?;
+
+        require!(lp > 0, AmmError::InvalidDeposit);
+        require!(lp >= min_lp, AmmError::SlippageExceeded);
+
+        let (dx, dy) = quote_deposit(x, y, supply, lp).ok_or(AmmError::InvalidDeposit)?;
+        let (deposit_in, deposit_out) = match is_x {
+            true => (dx, dy),
+            false => (dy, dx),
+        };
+
+        require!(
+            deposit_in <= remaining_in && deposit_out <= quote.amount_out,
+            AmmError::SlippageExceeded
+        );
+
+        // As in `swap`, the protocol's share is moved out of the vault rather
+        // than paid separately.
+        self.deposit_tokens(amount_in, is_x)?;
+        if quote.protocol_fee > 0 {
+            self.collect_protocol_fee(quote.protocol_fee, is_x)?;
+        }
+
+        // Whatever of the input and the swap output wasn't needed for the
+        // balanced deposit is returned to the user.
+        let refund_in = remaining_in - deposit_in;
+        if refund_in > 0 {
+            self.withdraw_tokens(refund_in, is_x)?;
+        }
+        let refund_out = quote.amount_out - deposit_out;
+        if refund_out > 0 {
+            self.withdraw_tokens(refund_out, !is_x)?;
+        }
+
+        self.mint_lp_tokens(lp)?;
+
+        emit!(SwapEvent {
+            pool: self.config.key(),
+            user: self.user.key(),
+            is_x,
+            amount_in: quote.amount_in,
+            amount_out: quote.amount_out,
+            fee: quote.fee,
+            protocol_fee: quote.protocol_fee,
+            fee_bps: self.config.total_fee()?,
+            price: quote.price().ok_or(AmmError::IncorrectK)?,
+            reserve_x: x,
+            reserve_y: y,
+            timestamp: clock.unix_timestamp,
+        });
+
+        emit!(DepositEvent {
+            pool: self.config.key(),
+            user: self.user.key(),
+            amount_x: dx,
+            amount_y: dy,
+            lp_minted: lp,
+            reserve_x: x.checked_add(dx).ok_or(AmmError::InvalidDeposit)?,
+            reserve_y: y.checked_add(dy).ok_or(AmmError::InvalidDeposit)?,
+            lp_supply: supply.checked_add(lp).ok_or(AmmError::InvalidDeposit)?,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    fn deposit_tokens(&self, amount: u64, is_x: bool) -> Result<()> {
+        let (from, to, mint, decimals) = match is_x {
+            true => (
+                self.user_x_ata.to_account_info(),
+                self.vault_x.to_account_info(),
+                self.mint_x.to_account_info(),
+                self.mint_x.decimals,
+            ),
+            false => (
+                self.user_y_ata.to_account_info(),
+                self.vault_y.to_account_info(),
+                self.mint_y.to_account_info(),
+                self.mint_y.decimals,
+            ),
+        };
+
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_accounts = TransferChecked {
+            from,
+            to,
+            authority: self.user.to_account_info(),
+            mint,
+        };
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+        transfer_checked(cpi_ctx, amount, decimals)
+    }
+
+    fn withdraw_tokens(&self, amount: u64, is_x: bool) -> Result<()> {
+        let (from, to, mint, decimals) = match is_x {
+            true => (
+                self.vault_x.to_account_info(),
+                self.user_x_ata.to_account_info(),
+                self.mint_x.to_account_info(),
+                self.mint_x.decimals,
+            ),
+            false => (
+                self.vault_y.to_account_info(),
+                self.user_y_ata.to_account_info(),
+                self.mint_y.to_account_info(),
+                self.mint_y.decimals,
+            ),
+        };
+
+        let seed = self.config.seed.to_le_bytes();
+        let seeds = &[
+            &b"config"[..],
+            seed.as_ref(),
+            self.config.mint_x.as_ref(),
+            self.config.mint_y.as_ref(),
+            &[self.config.config_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_accounts = TransferChecked {
+            from,
+            to,
+            authority: self.config.to_account_info(),
+            mint,
+        };
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        transfer_checked(cpi_ctx, amount, decimals)
+    }
+
+    fn collect_protocol_fee(&self, amount: u64, is_x: bool) -> Result<()> {
+        let seed = self.config.seed.to_le_bytes();
+        let seeds = &[
+            &b"config"[..],
+            seed.as_ref(),
+            self.config.mint_x.as_ref(),
+            self.config.mint_y.as_ref(),
+            &[self.config.config_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let (from, to, mint, decimals) = match is_x {
+            true => (
+                self.vault_x.to_account_info(),
+                self.fee_vault_x.to_account_info(),
+                self.mint_x.to_account_info(),
+                self.mint_x.decimals,
+            ),
+            false => (
+                self.vault_y.to_account_info(),
+                self.fee_vault_y.to_account_info(),
+                self.mint_y.to_account_info(),
+                self.mint_y.decimals,
+            ),
+        };
+
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_accounts = TransferChecked {
+            from,
+            to,
+            authority: self.config.to_account_info(),
+            mint,
+        };
+        let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        transfer_checked(cpi_context, amount, decimals)
+    }
+
+    fn mint_lp_tokens(&self, amount: u64) -> Result<()> {
+        let config_key = self.config.key();
+        let seeds = &[&b"auth"[..], config_key.as_ref(), &[self.config.auth_bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_accounts = MintTo {
+            mint: self.mint_lp.to_account_info(),
+            to: self.user_lp_ata.to_account_info(),
+            authority: self.auth.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        mint_to(cpi_ctx, amount)
+    }
+}
@@ -0,0 +1,250 @@
+use amm_math::div_ceil;
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        instruction::{get_stack_height, TRANSACTION_LEVEL_STACK_HEIGHT},
+        sysvar::instructions::{load_current_index_checked, load_instruction_at_checked},
+    },
+    Discriminator,
+};
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+use crate::{token::gross_amount, AmmError, Config, FlashLoanEvent};
+
+/// Position of `config` in the accounts of `flash_repay`.
+const REPAY_CONFIG_INDEX: usize = 1;
+
+#[derive(Accounts)]
+pub struct FlashLoan<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [
+            b"config",
+            config.seed.to_le_bytes().as_ref(),
+            mint_x.key().as_ref(),
+            mint_y.key().as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    pub mint_x: InterfaceAccount<'info, Mint>,
+    pub mint_y: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = mint_x,
+        token::authority = user,
+    )]
+    user_x_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = mint_y,
+        token::authority = user,
+    )]
+    user_y_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = config.vault_x,
+        token::mint = mint_x,
+        token::authority = config,
+    )]
+    vault_x: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = config.vault_y,
+        token::mint = mint_y,
+        token::authority = config,
+    )]
+    vault_y: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: address checked against the instructions sysvar
+    pub instructions: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> FlashLoan<'info> {
+    /// Lends `amount` of X (`is_x`) or Y from the pool vault. The transaction
+    /// must call `flash_repay` on the same pool later on; until then the pool
+    /// refuses every other operation.
+    pub fn flash_loan(&mut self, amount: u64, is_x: bool) -> Result<()> {
+        self.config.check_unlocked()?;
+        require!(amount > 0, AmmError::InvalidAmount);
+
+        // Introspection only sees top-level instructions, so a loan taken
+        // through CPI could not be matched to its repayment.
+        require!(
+            get_stack_height() == TRANSACTION_LEVEL_STACK_HEIGHT,
+            AmmError::InvalidFlashLoan
+        );
+        self.check_repayment()?;
+
+        self.config.flash_loan_amount = amount;
+        self.config.flash_loan_is_x = is_x;
+
+        self.withdraw_tokens(amount, is_x)?;
+
+        self.emit_flash_loan(false, is_x, amount)
+    }
+
+    /// Pays back the outstanding flash loan plus `flash_loan_fee`, which
+    /// stays in the vault for LPs. Transfer fees are paid on top, and the
+    /// pool vault must have received the full repayment.
+    pub fn flash_repay(&mut self) -> Result<()> {
+        let amount = self.config.flash_loan_amount;
+        require!(amount > 0, AmmError::InvalidFlashLoan);
+
+        let fee = self.flash_loan_fee(amount)?;
+        let repayment = amount.checked_add(fee).ok_or(AmmError::InvalidAmount)?;
+
+        let is_x = self.config.flash_loan_is_x;
+        self.config.flash_loan_amount = 0;
+        self.config.flash_loan_is_x = false;
+
+        let before = self.vault_balance(is_x);
+        self.deposit_tokens(repayment, is_x)?;
+        self.vault_x.reload()?;
+        self.vault_y.reload()?;
+
+        let received = self
+            .vault_balance(is_x)
+            .checked_sub(before)
+            .ok_or(AmmError::InvalidFlashLoan)?;
+        require!(received >= repayment, AmmError::InvalidFlashLoan);
+
+        self.emit_flash_loan(true, is_x, amount)
+    }
+
+    fn flash_loan_fee(&self, amount: u64) -> Result<u64> {
+        let fee = div_ceil(amount as u128 * self.config.flash_loan_fee as u128, 10_000)
+            .and_then(|fee| u64::try_from(fee).ok())
+            .ok_or(AmmError::InvalidAmount)?;
+
+        Ok(fee)
+    }
+
+    fn emit_flash_loan(&self, repaid: bool, is_x: bool, amount: u64) -> Result<()> {
+        emit!(FlashLoanEvent {
+            pool: self.config.key(),
+            user: self.user.key(),
+            repaid,
+            is_x,
+            amount,
+            fee: self.flash_loan_fee(amount)?,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    fn vault_balance(&self, is_x: bool) -> u64 {
+        match is_x {
+            true => self.vault_x.amount,
+            false => self.vault_y.amount,
+        }
+    }
+
+    /// Finds a `flash_repay` for this pool after the current instruction.
+    fn check_repayment(&self) -> Result<()> {
+        let instructions = self.instructions.to_account_info();
+        let current = load_current_index_checked(&instructions)? as usize;
+
+        let mut index = current + 1;
+        while let Ok(instruction) = load_instruction_at_checked(index, &instructions) {
+            let is_repay = instruction.program_id == crate::ID
+                && instruction
+                    .data
+                    .starts_with(&crate::instruction::FlashRepay::DISCRIMINATOR)
+                && instruction
+                    .accounts
+                    .get(REPAY_CONFIG_INDEX)
+                    .is_some_and(|meta| meta.pubkey == self.config.key());
+
+            if is_repay {
+                return Ok(());
+            }
+            index += 1;
+        }
+
+        err!(AmmError::InvalidFlashLoan)
+    }
+
+    fn deposit_tokens(&self, amount: u64, is_x: bool) -> Result<()> {
+        let (from, to, mint, decimals) = match is_x {
+            true => (
+                self.user_x_ata.to_account_info(),
+                self.vault_x.to_account_info(),
+                self.mint_x.to_account_info(),
+                self.mint_x.decimals,
+            ),
+            false => (
+                self.user_y_ata.to_account_info(),
+                self.vault_y.to_account_info(),
+                self.mint_y.to_account_info(),
+                self.mint_y.decimals,
+            ),
+        };
+
+        let amount = gross_amount(&mint, amount)?;
+
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_accounts = TransferChecked {
+            from,
+            to,
+            authority: self.user.to_account_info(),
+            mint,
+        };
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+        transfer_checked(cpi_ctx, amount, decimals)
+    }
+
+    fn withdraw_tokens(&self, amount: u64, is_x: bool) -> Result<()> {
+        let (from, to, mint, decimals) = match is_x {
+            true => (
+                self.vault_x.to_account_info(),
+                self.user_x_ata.to_account_info(),
+                self.mint_x.to_account_info(),
+                self.mint_x.decimals,
+            ),
+            false => (
+                self.vault_y.to_account_info(),
+                self.user_y_ata.to_account_info(),
+                self.mint_y.to_account_info(),
+                self.mint_y.decimals,
+            ),
+        };
+
+        let seed = self.config.seed.to_le_bytes();
+        let seeds = &[
+            &b"config"[..],
+            seed.as_ref(),
+            self.config.mint_x.as_ref(),
+            self.config.mint_y.as_ref(),
+            &[self.config.config_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_accounts = TransferChecked {
+            from,
+            to,
+            authority: self.config.to_account_info(),
+            mint,
+        };
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        transfer_checked(cpi_ctx, amount, decimals)
+    }
+}
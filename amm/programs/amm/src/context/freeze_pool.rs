@@ -0,0 +1,73 @@
+use anchor_lang::prelude::*;
+
+use crate::{AmmError, Config, CurveType, PairIndex, PoolFrozenEvent, Registry};
+
+#[derive(Accounts)]
+pub struct FreezePool<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = authority,
+        constraint = config.curve != CurveType::Concentrated @ AmmError::InvalidCurve,
+        seeds = [
+            b"config",
+            config.seed.to_le_bytes().as_ref(),
+            config.mint_x.as_ref(),
+            config.mint_y.as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"pair",
+            config.mint_x.min(config.mint_y).as_ref(),
+            config.mint_x.max(config.mint_y).as_ref()
+        ],
+        bump = pair.bump,
+    )]
+    pub pair: Account<'info, PairIndex>,
+
+    #[account(
+        mut,
+        seeds = [b"registry"],
+        bump = registry.bump,
+    )]
+    pub registry: Account<'info, Registry>,
+}
+
+impl<'info> FreezePool<'info> {
+    /// First step of closing a pool: locks it for good and delists it.
+    ///
+    /// Swaps, deposits and staking stop, but holders keep withdrawing their
+    /// share of the reserves at their own pace until `close_pool` settles
+    /// whatever LP is left.
+    pub fn freeze_pool(&mut self) -> Result<()> {
+        require!(!self.config.closing, AmmError::PoolFrozen);
+        require!(
+            self.config.flash_loan_amount == 0,
+            AmmError::FlashLoanActive
+        );
+
+        self.config.locked = true;
+        self.config.closing = true;
+
+        let config = self.config.key();
+        self.pair.remove(config);
+        self.registry.pools -= 1;
+        if self.pair.pools.is_empty() {
+            self.registry.pairs -= 1;
+        }
+
+        emit!(PoolFrozenEvent {
+            pool: config,
+            authority: self.authority.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
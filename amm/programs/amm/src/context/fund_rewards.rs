@@ -0,0 +1,87 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+use crate::{token::net_amount, AmmError, Config, Farm, RewardsFundedEvent};
+
+#[derive(Accounts)]
+pub struct FundRewards<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        has_one = authority,
+        seeds = [
+            b"config",
+            config.seed.to_le_bytes().as_ref(),
+            config.mint_x.as_ref(),
+            config.mint_y.as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        has_one = config,
+        has_one = reward_mint,
+        seeds = [b"farm", config.key().as_ref()],
+        bump = farm.bump,
+    )]
+    pub farm: Account<'info, Farm>,
+
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = reward_mint,
+        token::authority = authority,
+    )]
+    pub authority_reward_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"farm_rewards", farm.key().as_ref()],
+        bump = farm.reward_vault_bump,
+    )]
+    pub reward_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> FundRewards<'info> {
+    /// Transfers `amount` of rewards into the farm and streams them, along
+    /// with any rewards not yet streamed, evenly over the next `duration`
+    /// seconds.
+    pub fn fund_rewards(&mut self, amount: u64, duration: i64) -> Result<()> {
+        require!(amount > 0, AmmError::InvalidAmount);
+
+        let now = Clock::get()?.unix_timestamp;
+        let received = net_amount(&self.reward_mint, amount)?;
+
+        self.farm.update(now)?;
+        self.farm.fund(received, duration, now)?;
+
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_accounts = TransferChecked {
+            from: self.authority_reward_ata.to_account_info(),
+            to: self.reward_vault.to_account_info(),
+            authority: self.authority.to_account_info(),
+            mint: self.reward_mint.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+        transfer_checked(cpi_ctx, amount, self.reward_mint.decimals)?;
+
+        emit!(RewardsFundedEvent {
+            farm: self.farm.key(),
+            authority: self.authority.key(),
+            amount: received,
+            reward_rate: self.farm.reward_rate,
+            reward_end_ts: self.farm.reward_end_ts,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+}
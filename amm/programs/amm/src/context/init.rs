@@ -5,7 +5,21 @@ use anchor_spl::{
     token_interface::{Mint, TokenAccount, TokenInterface},
 };
 
-use crate::{AmmError, Config};
+use crate::{
+    token::check_mint, AmmError, Config, CurveType, InitializeEvent, Observation, Oracle,
+    PairIndex, Registry, CONFIG_VERSION, OBSERVATIONS,
+};
+
+/// Parameters of a pool created by `initialize_amm`.
+pub struct InitArgs {
+    pub seed: u64,
+    pub fee: u16,
+    pub protocol_fee: u16,
+    pub curve: CurveType,
+    /// StableSwap amplification coefficient, ignored by other curves.
+    pub amp: u64,
+    pub flash_loan_fee: u16,
+}
 
 #[derive(Accounts)]
 #[instruction(seed: u64)]
@@ -33,12 +47,30 @@ pub struct Init<'info> {
     pub mint_lp: InterfaceAccount<'info, Mint>,
 
     #[account(
-        seeds = [b"auth"],
+        seeds = [b"auth", config.key().as_ref()],
         bump,
     )]
     /// CHECK: checked via constraints
     pub auth: UncheckedAccount<'info>,
 
+    #[account(
+        seeds = [b"dead", config.key().as_ref()],
+        bump,
+    )]
+    /// CHECK: signs only to burn its LP in `close_pool`, so LP held by it
+    /// cannot move while the pool is open
+    pub dead: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = initializer,
+        seeds = [b"lp_lock", config.key().as_ref()],
+        bump,
+        token::mint = mint_lp,
+        token::authority = dead,
+    )]
+    pub lp_lock: InterfaceAccount<'info, TokenAccount>,
+
     pub mint_x: InterfaceAccount<'info, Mint>,
     pub mint_y: InterfaceAccount<'info, Mint>,
 
@@ -58,27 +90,163 @@ pub struct Init<'info> {
     )]
     pub vault_y: InterfaceAccount<'info, TokenAccount>,
 
+    #[account(
+        init,
+        payer = initializer,
+        seeds = [b"fee_x", config.key().as_ref()],
+        bump,
+        token::mint = mint_x,
+        token::authority = config,
+    )]
+    pub fee_vault_x: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = initializer,
+        seeds = [b"fee_y", config.key().as_ref()],
+        bump,
+        token::mint = mint_y,
+        token::authority = config,
+    )]
+    pub fee_vault_y: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = initializer,
+        space = Oracle::INIT_SPACE,
+        seeds = [b"oracle", config.key().as_ref()],
+        bump,
+    )]
+    pub oracle: Account<'info, Oracle>,
+
+    #[account(
+        init_if_needed,
+        payer = initializer,
+        space = Registry::INIT_SPACE,
+        seeds = [b"registry"],
+        bump,
+    )]
+    pub registry: Account<'info, Registry>,
+
+    #[account(
+        init_if_needed,
+        payer = initializer,
+        space = PairIndex::INIT_SPACE,
+        seeds = [b"pair", mint_x.key().as_ref(), mint_y.key().as_ref()],
+        bump,
+    )]
+    pub pair: Account<'info, PairIndex>,
+
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
 impl<'info> Init<'info> {
-    pub fn init(&mut self, seed: u64, fee: u16, bumps: &InitBumps) -> Result<()> {
-        require!(fee <= 10_000, AmmError::FeeTooHigh);
+    pub fn init(&mut self, args: InitArgs, bumps: &InitBumps) -> Result<()> {
+        let InitArgs {
+            seed,
+            fee,
+            protocol_fee,
+            curve,
+            amp,
+            flash_loan_fee,
+        } = args;
+
+        Config::check_fees(fee, protocol_fee)?;
+        Config::check_flash_loan_fee(flash_loan_fee)?;
+        check_mint(&self.mint_x)?;
+        check_mint(&self.mint_y)?;
+        require!(
+            self.mint_x.key() < self.mint_y.key(),
+            AmmError::InvalidMintOrder
+        );
+
+        let amp = match curve {
+            CurveType::ConstantProduct | CurveType::Concentrated => 0,
+            CurveType::StableSwap => {
+                Config::check_amp(amp)?;
+                amp
+            }
+        };
 
         self.config.set_inner(Config {
+            version: CONFIG_VERSION,
             seed,
-            authority: self.auth.key(),
+            authority: self.initializer.key(),
+            pending_authority: None,
             mint_x: self.mint_x.key(),
             mint_y: self.mint_y.key(),
+            vault_x: self.vault_x.key(),
+            vault_y: self.vault_y.key(),
             fee,
+            protocol_fee,
             locked: false,
+            closing: false,
+            curve,
+            initial_amp: amp,
+            target_amp: amp,
+            ramp_start_ts: 0,
+            ramp_stop_ts: 0,
+            flash_loan_fee,
+            flash_loan_amount: 0,
+            flash_loan_is_x: false,
+            dynamic_fee: false,
+            min_fee: 0,
+            max_fee: 0,
+            volatility: 0,
+            volatility_updated_ts: 0,
+            max_trade_bps: 0,
             config_bump: bumps.config,
             lp_mint_bump: bumps.mint_lp,
             auth_bump: bumps.auth,
+            fee_vault_x_bump: bumps.fee_vault_x,
+            fee_vault_y_bump: bumps.fee_vault_y,
+            lp_lock_bump: bumps.lp_lock,
+            oracle_bump: bumps.oracle,
+        });
+
+        let timestamp = Clock::get()?.unix_timestamp;
+
+        self.oracle.set_inner(Oracle {
+            price_x_cumulative: 0,
+            price_y_cumulative: 0,
+            last_updated: timestamp,
+            observation_index: 0,
+            observations: [Observation::default(); OBSERVATIONS],
+        });
+
+        let canonical = self.register(curve, fee, bumps)?;
+
+        emit!(InitializeEvent {
+            pool: self.config.key(),
+            authority: self.initializer.key(),
+            mint_x: self.mint_x.key(),
+            mint_y: self.mint_y.key(),
+            mint_lp: self.mint_lp.key(),
+            curve,
+            fee,
+            protocol_fee,
+            canonical,
+            timestamp,
         });
 
         Ok(())
     }
+
+    /// Lists the pool in the registry and its pair index, creating the
+    /// index on the pair's first pool.
+    fn register(&mut self, curve: CurveType, fee: u16, bumps: &InitBumps) -> Result<bool> {
+        if self.pair.pools.is_empty() {
+            self.pair.mint_x = self.mint_x.key();
+            self.pair.mint_y = self.mint_y.key();
+            self.pair.bump = bumps.pair;
+            self.registry.pairs += 1;
+        }
+
+        self.registry.pools += 1;
+        self.registry.bump = bumps.registry;
+
+        self.pair.add(self.config.key(), curve, fee)
+    }
 }
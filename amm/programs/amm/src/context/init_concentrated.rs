@@ -0,0 +1,73 @@
+use amm_math::tick_at_sqrt_price;
+use anchor_lang::prelude::*;
+
+use crate::{AmmError, ConcentratedPool, Config, CurveType, TickBitmap, MIN_TICK_SPACING};
+
+#[derive(Accounts)]
+pub struct InitConcentrated<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        has_one = authority,
+        constraint = config.curve == CurveType::Concentrated @ AmmError::InvalidCurve,
+        seeds = [
+            b"config",
+            config.seed.to_le_bytes().as_ref(),
+            config.mint_x.as_ref(),
+            config.mint_y.as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = ConcentratedPool::INIT_SPACE,
+        seeds = [b"concentrated", config.key().as_ref()],
+        bump,
+    )]
+    pub pool: Account<'info, ConcentratedPool>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = TickBitmap::INIT_SPACE,
+        seeds = [b"bitmap", config.key().as_ref()],
+        bump,
+    )]
+    pub bitmap: AccountLoader<'info, TickBitmap>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitConcentrated<'info> {
+    /// Sets the starting price, as a Q64.64 sqrt price of X in Y, and the tick
+    /// spacing of a concentrated pool.
+    pub fn init_concentrated(
+        &mut self,
+        tick_spacing: u16,
+        sqrt_price: u128,
+        bumps: &InitConcentratedBumps,
+    ) -> Result<()> {
+        require!(tick_spacing >= MIN_TICK_SPACING, AmmError::InvalidTick);
+
+        let tick_current = tick_at_sqrt_price(sqrt_price).ok_or(AmmError::InvalidTick)?;
+
+        self.pool.set_inner(ConcentratedPool {
+            sqrt_price,
+            tick_current,
+            tick_spacing,
+            liquidity: 0,
+            fee_growth_global_x: 0,
+            fee_growth_global_y: 0,
+            bump: bumps.pool,
+            bitmap_bump: bumps.bitmap,
+        });
+
+        self.bitmap.load_init()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,88 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{token::check_mint, Config, Farm};
+
+#[derive(Accounts)]
+pub struct InitFarm<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        has_one = authority,
+        seeds = [
+            b"config",
+            config.seed.to_le_bytes().as_ref(),
+            config.mint_x.as_ref(),
+            config.mint_y.as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [b"lp", config.key().as_ref()],
+        bump = config.lp_mint_bump,
+    )]
+    pub mint_lp: InterfaceAccount<'info, Mint>,
+
+    /// Rewards must be a mint of the same token program as the pool's LP.
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = Farm::INIT_SPACE,
+        seeds = [b"farm", config.key().as_ref()],
+        bump,
+    )]
+    pub farm: Account<'info, Farm>,
+
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"farm_lp", farm.key().as_ref()],
+        bump,
+        token::mint = mint_lp,
+        token::authority = farm,
+    )]
+    pub stake_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"farm_rewards", farm.key().as_ref()],
+        bump,
+        token::mint = reward_mint,
+        token::authority = farm,
+    )]
+    pub reward_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitFarm<'info> {
+    /// Opens an unfunded farm for the pool's LP. Rewards start streaming once
+    /// the authority calls `fund_rewards`.
+    pub fn init_farm(&mut self, bumps: &InitFarmBumps) -> Result<()> {
+        check_mint(&self.reward_mint)?;
+
+        let now = Clock::get()?.unix_timestamp;
+
+        self.farm.set_inner(Farm {
+            config: self.config.key(),
+            reward_mint: self.reward_mint.key(),
+            reward_rate: 0,
+            reward_end_ts: now,
+            last_update_ts: now,
+            reward_per_share: 0,
+            total_staked: 0,
+            bump: bumps.farm,
+            stake_vault_bump: bumps.stake_vault,
+            reward_vault_bump: bumps.reward_vault,
+        });
+
+        Ok(())
+    }
+}
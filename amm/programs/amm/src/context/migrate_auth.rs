@@ -0,0 +1,283 @@
+use anchor_lang::{prelude::*, solana_program::program_option::COption};
+use anchor_spl::{
+    token_2022::spl_token_2022::instruction::AuthorityType,
+    token_interface::{set_authority, Mint, SetAuthority, TokenAccount, TokenInterface},
+};
+
+use crate::{
+    program::Amm, AmmError, Config, CurveType, LegacyConfig, Observation, Oracle, PairIndex,
+    Registry, CONFIG_VERSION, OBSERVATIONS,
+};
+
+/// Brings a pool created before per-pool auth PDAs up to date: moves its LP
+/// mint from the program-wide `[b"auth"]` PDA to `[b"auth", config]`, rewrites
+/// its config in the current layout and creates the accounts later versions
+/// added.
+///
+/// Legacy pools were administered by the program-wide PDA, which no one can
+/// sign for, so the program's upgrade authority runs the migration and
+/// becomes the pool authority.
+#[derive(Accounts)]
+pub struct MigrateAuth<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        constraint = program.programdata_address()? == Some(program_data.key()) @ AmmError::InvalidAuthority,
+    )]
+    pub program: Program<'info, Amm>,
+
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(admin.key()) @ AmmError::InvalidAuthority,
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    #[account(
+        mut,
+        owner = crate::ID,
+    )]
+    /// CHECK: a legacy config is too short to load as `Config`; it is decoded
+    /// as `LegacyConfig` and checked against its seeds in the handler
+    pub config: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"lp", config.key().as_ref()],
+        bump,
+        constraint = mint_lp.mint_authority == COption::Some(legacy_auth.key()) @ AmmError::AlreadyMigrated,
+    )]
+    pub mint_lp: InterfaceAccount<'info, Mint>,
+
+    pub mint_x: InterfaceAccount<'info, Mint>,
+    pub mint_y: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [b"auth"],
+        bump,
+    )]
+    /// CHECK: program-wide mint authority used by legacy pools
+    pub legacy_auth: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"auth", config.key().as_ref()],
+        bump,
+    )]
+    /// CHECK: checked via constraints
+    pub auth: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"dead", config.key().as_ref()],
+        bump,
+    )]
+    /// CHECK: signs only to burn its LP in `close_pool`
+    pub dead: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"lp_lock", config.key().as_ref()],
+        bump,
+        token::mint = mint_lp,
+        token::authority = dead,
+    )]
+    pub lp_lock: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        token::mint = mint_x,
+        token::authority = config,
+    )]
+    pub vault_x: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        token::mint = mint_y,
+        token::authority = config,
+    )]
+    pub vault_y: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"fee_x", config.key().as_ref()],
+        bump,
+        token::mint = mint_x,
+        token::authority = config,
+    )]
+    pub fee_vault_x: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"fee_y", config.key().as_ref()],
+        bump,
+        token::mint = mint_y,
+        token::authority = config,
+    )]
+    pub fee_vault_y: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = Oracle::INIT_SPACE,
+        seeds = [b"oracle", config.key().as_ref()],
+        bump,
+    )]
+    pub oracle: Account<'info, Oracle>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = Registry::INIT_SPACE,
+        seeds = [b"registry"],
+        bump,
+    )]
+    pub registry: Account<'info, Registry>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = PairIndex::INIT_SPACE,
+        seeds = [
+            b"pair",
+            mint_x.key().min(mint_y.key()).as_ref(),
+            mint_x.key().max(mint_y.key()).as_ref()
+        ],
+        bump,
+    )]
+    pub pair: Account<'info, PairIndex>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MigrateAuth<'info> {
+    /// The vaults are the keypair accounts the legacy pool was initialized
+    /// with; they hold its reserves and are pinned in the config from here
+    /// on. Legacy fees become the LP fee, with no protocol fee.
+    pub fn migrate_auth(&mut self, bumps: &MigrateAuthBumps) -> Result<()> {
+        let legacy = LegacyConfig::try_from_account_data(&self.config.try_borrow_data()?)?;
+
+        let config_key = Pubkey::create_program_address(
+            &[
+                b"config",
+                legacy.seed.to_le_bytes().as_ref(),
+                legacy.mint_x.as_ref(),
+                legacy.mint_y.as_ref(),
+                &[legacy.config_bump],
+            ],
+            &crate::ID,
+        )
+        .map_err(|_| error!(ErrorCode::ConstraintSeeds))?;
+        require_keys_eq!(config_key, self.config.key(), ErrorCode::ConstraintSeeds);
+        require_keys_eq!(
+            legacy.mint_x,
+            self.mint_x.key(),
+            ErrorCode::ConstraintHasOne
+        );
+        require_keys_eq!(
+            legacy.mint_y,
+            self.mint_y.key(),
+            ErrorCode::ConstraintHasOne
+        );
+        require!(
+            legacy.lp_mint_bump == bumps.mint_lp,
+            ErrorCode::ConstraintSeeds
+        );
+
+        self.migrate_mint_authority(bumps.legacy_auth)?;
+
+        Config::realloc(
+            &self.config.to_account_info(),
+            &self.admin.to_account_info(),
+            &self.system_program.to_account_info(),
+        )?;
+
+        let config = Config {
+            version: CONFIG_VERSION,
+            seed: legacy.seed,
+            authority: self.admin.key(),
+            pending_authority: None,
+            mint_x: legacy.mint_x,
+            mint_y: legacy.mint_y,
+            vault_x: self.vault_x.key(),
+            vault_y: self.vault_y.key(),
+            fee: legacy.fee,
+            protocol_fee: 0,
+            locked: legacy.locked,
+            closing: false,
+            curve: CurveType::ConstantProduct,
+            initial_amp: 0,
+            target_amp: 0,
+            ramp_start_ts: 0,
+            ramp_stop_ts: 0,
+            flash_loan_fee: 0,
+            flash_loan_amount: 0,
+            flash_loan_is_x: false,
+            dynamic_fee: false,
+            min_fee: 0,
+            max_fee: 0,
+            volatility: 0,
+            volatility_updated_ts: 0,
+            max_trade_bps: 0,
+            config_bump: legacy.config_bump,
+            lp_mint_bump: legacy.lp_mint_bump,
+            auth_bump: bumps.auth,
+            fee_vault_x_bump: bumps.fee_vault_x,
+            fee_vault_y_bump: bumps.fee_vault_y,
+            lp_lock_bump: bumps.lp_lock,
+            oracle_bump: bumps.oracle,
+        };
+
+        {
+            let mut data = self.config.try_borrow_mut_data()?;
+            let mut writer: &mut [u8] = &mut data;
+            config.try_serialize(&mut writer)?;
+        }
+
+        self.oracle.set_inner(Oracle {
+            price_x_cumulative: 0,
+            price_y_cumulative: 0,
+            last_updated: Clock::get()?.unix_timestamp,
+            observation_index: 0,
+            observations: [Observation::default(); OBSERVATIONS],
+        });
+
+        self.register(legacy.fee, bumps)?;
+
+        Ok(())
+    }
+
+    fn migrate_mint_authority(&self, legacy_auth_bump: u8) -> Result<()> {
+        let seeds = &[&b"auth"[..], &[legacy_auth_bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_accounts = SetAuthority {
+            current_authority: self.legacy_auth.to_account_info(),
+            account_or_mint: self.mint_lp.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        set_authority(cpi_ctx, AuthorityType::MintTokens, Some(self.auth.key()))
+    }
+
+    /// Lists the pool like `initialize_amm` does. Legacy pools did not have
+    /// to order their mints, so they are sorted to find the pair index newer
+    /// pools of the same mints share.
+    fn register(&mut self, fee: u16, bumps: &MigrateAuthBumps) -> Result<()> {
+        if self.pair.pools.is_empty() {
+            let (mint_x, mint_y) = (self.mint_x.key(), self.mint_y.key());
+            self.pair.mint_x = mint_x.min(mint_y);
+            self.pair.mint_y = mint_x.max(mint_y);
+            self.pair.bump = bumps.pair;
+            self.registry.pairs += 1;
+        }
+
+        self.registry.pools += 1;
+        self.registry.bump = bumps.registry;
+
+        self.pair
+            .add(self.config.key(), CurveType::ConstantProduct, fee)?;
+
+        Ok(())
+    }
+}
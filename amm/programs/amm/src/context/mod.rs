@@ -1,9 +1,49 @@
+pub mod accept_authority;
+pub mod admin;
+pub mod claim_rewards;
+pub mod close_pool;
+pub mod close_position;
+pub mod collect_protocol_fees;
 pub mod deposit;
+pub mod deposit_single;
+pub mod flash_loan;
+pub mod freeze_pool;
+pub mod fund_rewards;
 pub mod init;
+pub mod init_concentrated;
+pub mod init_farm;
+pub mod migrate_auth;
+pub mod modify_position;
+pub mod observe;
+pub mod open_position;
+pub mod stake;
 pub mod swap;
+pub mod swap_concentrated;
+pub mod swap_route;
 pub mod withdraw;
+pub mod withdraw_single;
 
+pub use accept_authority::*;
+pub use admin::*;
+pub use claim_rewards::*;
+pub use close_pool::*;
+pub use close_position::*;
+pub use collect_protocol_fees::*;
 pub use deposit::*;
+pub use deposit_single::*;
+pub use flash_loan::*;
+pub use freeze_pool::*;
+pub use fund_rewards::*;
 pub use init::*;
+pub use init_concentrated::*;
+pub use init_farm::*;
+pub use migrate_auth::*;
+pub use modify_position::*;
+pub use observe::*;
+pub use open_position::*;
+pub use stake::*;
 pub use swap::*;
+pub use swap_concentrated::*;
+pub use swap_route::*;
 pub use withdraw::*;
+pub use withdraw_single::*;
@@ -0,0 +1,347 @@
+use amm_math::{add_liquidity_delta, position_amounts};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+use crate::{
+    check_deadline,
+    token::{gross_amount, net_amount},
+    AmmError, ConcentratedPool, Config, Position, PositionFeesCollectedEvent,
+    PositionLiquidityEvent, Tick, TickBitmap,
+};
+
+#[derive(Accounts)]
+pub struct ModifyPosition<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [
+            b"config",
+            config.seed.to_le_bytes().as_ref(),
+            mint_x.key().as_ref(),
+            mint_y.key().as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    pub mint_x: InterfaceAccount<'info, Mint>,
+    pub mint_y: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"concentrated", config.key().as_ref()],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, ConcentratedPool>,
+
+    #[account(
+        mut,
+        seeds = [b"bitmap", config.key().as_ref()],
+        bump = pool.bitmap_bump,
+    )]
+    pub bitmap: AccountLoader<'info, TickBitmap>,
+
+    #[account(
+        mut,
+        has_one = owner,
+        has_one = config,
+        seeds = [
+            b"position",
+            config.key().as_ref(),
+            owner.key().as_ref(),
+            position.tick_lower.to_le_bytes().as_ref(),
+            position.tick_upper.to_le_bytes().as_ref()
+        ],
+        bump = position.bump,
+    )]
+    pub position: Account<'info, Position>,
+
+    #[account(
+        mut,
+        seeds = [b"tick", config.key().as_ref(), position.tick_lower.to_le_bytes().as_ref()],
+        bump = lower_tick.bump,
+    )]
+    pub lower_tick: Account<'info, Tick>,
+
+    #[account(
+        mut,
+        seeds = [b"tick", config.key().as_ref(), position.tick_upper.to_le_bytes().as_ref()],
+        bump = upper_tick.bump,
+    )]
+    pub upper_tick: Account<'info, Tick>,
+
+    #[account(
+        mut,
+        token::mint = mint_x,
+        token::authority = owner,
+    )]
+    user_x_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = mint_y,
+        token::authority = owner,
+    )]
+    user_y_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = config.vault_x,
+        token::mint = mint_x,
+        token::authority = config,
+    )]
+    vault_x: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = config.vault_y,
+        token::mint = mint_y,
+        token::authority = config,
+    )]
+    vault_y: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> ModifyPosition<'info> {
+    /// Adds `liquidity` to the position, paying at most `max_x`/`max_y`.
+    pub fn increase_liquidity(
+        &mut self,
+        liquidity: u128,
+        max_x: u64,
+        max_y: u64,
+        expiration: i64,
+        max_slot: Option<u64>,
+    ) -> Result<()> {
+        self.config.check_unlocked()?;
+        check_deadline(&Clock::get()?, expiration, max_slot)?;
+        require!(liquidity > 0, AmmError::InvalidAmount);
+
+        let (x, y) = self.position_amounts(liquidity, true)?;
+        require!(
+            gross_amount(&self.mint_x, x)? <= max_x && gross_amount(&self.mint_y, y)? <= max_y,
+            AmmError::SlippageExceeded
+        );
+
+        let delta = i128::try_from(liquidity).map_err(|_| AmmError::InvalidAmount)?;
+        self.modify(delta)?;
+
+        if x > 0 {
+            self.deposit_tokens(x, true)?;
+        }
+        if y > 0 {
+            self.deposit_tokens(y, false)?;
+        }
+
+        self.emit_liquidity(true, liquidity, x, y)
+    }
+
+    /// Removes `liquidity` from the position and pays out the tokens backing
+    /// it. Fees stay on the position until collected.
+    pub fn decrease_liquidity(
+        &mut self,
+        liquidity: u128,
+        min_x: u64,
+        min_y: u64,
+        expiration: i64,
+        max_slot: Option<u64>,
+    ) -> Result<()> {
+        self.config.check_unlocked()?;
+        check_deadline(&Clock::get()?, expiration, max_slot)?;
+        require!(
+            liquidity > 0 && liquidity <= self.position.liquidity,
+            AmmError::InvalidAmount
+        );
+
+        let (x, y) = self.position_amounts(liquidity, false)?;
+        require!(
+            net_amount(&self.mint_x, x)? >= min_x && net_amount(&self.mint_y, y)? >= min_y,
+            AmmError::SlippageExceeded
+        );
+
+        let delta = i128::try_from(liquidity).map_err(|_| AmmError::InvalidAmount)?;
+        self.modify(-delta)?;
+
+        if x > 0 {
+            self.withdraw_tokens(x, true)?;
+        }
+        if y > 0 {
+            self.withdraw_tokens(y, false)?;
+        }
+
+        self.emit_liquidity(false, liquidity, x, y)
+    }
+
+    /// Pays out the fees the position has earned so far.
+    pub fn collect_fees(&mut self) -> Result<()> {
+        self.config.check_unlocked()?;
+
+        self.modify(0)?;
+
+        let (x, y) = (self.position.fees_owed_x, self.position.fees_owed_y);
+        self.position.fees_owed_x = 0;
+        self.position.fees_owed_y = 0;
+
+        if x > 0 {
+            self.withdraw_tokens(x, true)?;
+        }
+        if y > 0 {
+            self.withdraw_tokens(y, false)?;
+        }
+
+        emit!(PositionFeesCollectedEvent {
+            pool: self.config.key(),
+            position: self.position.key(),
+            owner: self.owner.key(),
+            amount_x: x,
+            amount_y: y,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    fn emit_liquidity(&self, increased: bool, liquidity: u128, x: u64, y: u64) -> Result<()> {
+        emit!(PositionLiquidityEvent {
+            pool: self.config.key(),
+            position: self.position.key(),
+            owner: self.owner.key(),
+            increased,
+            liquidity_delta: liquidity,
+            amount_x: x,
+            amount_y: y,
+            liquidity: self.position.liquidity,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    fn position_amounts(&self, liquidity: u128, round_up: bool) -> Result<(u64, u64)> {
+        let amounts = position_amounts(
+            self.pool.sqrt_price,
+            self.pool.tick_current,
+            self.position.tick_lower,
+            self.position.tick_upper,
+            liquidity,
+            round_up,
+        )
+        .ok_or(AmmError::InvalidAmount)?;
+
+        Ok(amounts)
+    }
+
+    /// Applies `liquidity_delta` to the position, its ticks and, if the
+    /// position is in range, the pool, crediting fees earned so far.
+    fn modify(&mut self, liquidity_delta: i128) -> Result<()> {
+        if liquidity_delta != 0 {
+            let flipped_lower = self.lower_tick.update(liquidity_delta, false, &self.pool)?;
+            let flipped_upper = self.upper_tick.update(liquidity_delta, true, &self.pool)?;
+
+            let mut bitmap = self.bitmap.load_mut()?;
+            if flipped_lower {
+                bitmap.set(
+                    self.lower_tick.index,
+                    self.pool.tick_spacing,
+                    self.lower_tick.liquidity_gross > 0,
+                );
+            }
+            if flipped_upper {
+                bitmap.set(
+                    self.upper_tick.index,
+                    self.pool.tick_spacing,
+                    self.upper_tick.liquidity_gross > 0,
+                );
+            }
+        }
+
+        let (inside_x, inside_y) = self
+            .pool
+            .fee_growth_inside(&self.lower_tick, &self.upper_tick);
+        self.position.accrue_fees(inside_x, inside_y)?;
+
+        self.position.liquidity = add_liquidity_delta(self.position.liquidity, liquidity_delta)
+            .ok_or(AmmError::InvalidAmount)?;
+
+        let in_range = self.pool.tick_current >= self.position.tick_lower
+            && self.pool.tick_current < self.position.tick_upper;
+        if in_range {
+            self.pool.liquidity = add_liquidity_delta(self.pool.liquidity, liquidity_delta)
+                .ok_or(AmmError::InvalidAmount)?;
+        }
+
+        Ok(())
+    }
+
+    fn deposit_tokens(&self, amount: u64, is_x: bool) -> Result<()> {
+        let (from, to, mint, decimals) = match is_x {
+            true => (
+                self.user_x_ata.to_account_info(),
+                self.vault_x.to_account_info(),
+                self.mint_x.to_account_info(),
+                self.mint_x.decimals,
+            ),
+            false => (
+                self.user_y_ata.to_account_info(),
+                self.vault_y.to_account_info(),
+                self.mint_y.to_account_info(),
+                self.mint_y.decimals,
+            ),
+        };
+
+        let amount = gross_amount(&mint, amount)?;
+
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_accounts = TransferChecked {
+            from,
+            to,
+            authority: self.owner.to_account_info(),
+            mint,
+        };
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+        transfer_checked(cpi_ctx, amount, decimals)
+    }
+
+    fn withdraw_tokens(&self, amount: u64, is_x: bool) -> Result<()> {
+        let (from, to, mint, decimals) = match is_x {
+            true => (
+                self.vault_x.to_account_info(),
+                self.user_x_ata.to_account_info(),
+                self.mint_x.to_account_info(),
+                self.mint_x.decimals,
+            ),
+            false => (
+                self.vault_y.to_account_info(),
+                self.user_y_ata.to_account_info(),
+                self.mint_y.to_account_info(),
+                self.mint_y.decimals,
+            ),
+        };
+
+        let seed = self.config.seed.to_le_bytes();
+        let seeds = &[
+            &b"config"[..],
+            seed.as_ref(),
+            self.config.mint_x.as_ref(),
+            self.config.mint_y.as_ref(),
+            &[self.config.config_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_accounts = TransferChecked {
+            from,
+            to,
+            authority: self.config.to_account_info(),
+            mint,
+        };
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        transfer_checked(cpi_ctx, amount, decimals)
+    }
+}
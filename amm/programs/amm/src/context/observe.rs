@@ -0,0 +1,56 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::{AmmError, Config, Oracle, Twap};
+
+#[derive(Accounts)]
+pub struct Observe<'info> {
+    #[account(
+        seeds = [
+            b"config",
+            config.seed.to_le_bytes().as_ref(),
+            config.mint_x.as_ref(),
+            config.mint_y.as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        address = config.vault_x,
+        token::mint = config.mint_x,
+        token::authority = config,
+    )]
+    vault_x: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        address = config.vault_y,
+        token::mint = config.mint_y,
+        token::authority = config,
+    )]
+    vault_y: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        seeds = [b"oracle", config.key().as_ref()],
+        bump = config.oracle_bump,
+    )]
+    oracle: Account<'info, Oracle>,
+}
+
+impl<'info> Observe<'info> {
+    /// Time-weighted average prices over at least the last `seconds_ago`
+    /// seconds, as Q64.64 fixed point. `window` is the span actually covered.
+    pub fn observe(&self, seconds_ago: u32) -> Result<Twap> {
+        let twap = self
+            .oracle
+            .observe(
+                self.vault_x.amount,
+                self.vault_y.amount,
+                Clock::get()?.unix_timestamp,
+                seconds_ago as i64,
+            )
+            .ok_or(AmmError::InsufficientObservations)?;
+
+        Ok(twap)
+    }
+}
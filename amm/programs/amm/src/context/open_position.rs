@@ -0,0 +1,104 @@
+use anchor_lang::prelude::*;
+
+use crate::{AmmError, ConcentratedPool, Config, Position, Tick};
+
+#[derive(Accounts)]
+#[instruction(tick_lower: i32, tick_upper: i32)]
+pub struct OpenPosition<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [
+            b"config",
+            config.seed.to_le_bytes().as_ref(),
+            config.mint_x.as_ref(),
+            config.mint_y.as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [b"concentrated", config.key().as_ref()],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, ConcentratedPool>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = Tick::INIT_SPACE,
+        seeds = [b"tick", config.key().as_ref(), tick_lower.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub lower_tick: Account<'info, Tick>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = Tick::INIT_SPACE,
+        seeds = [b"tick", config.key().as_ref(), tick_upper.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub upper_tick: Account<'info, Tick>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = Position::INIT_SPACE,
+        seeds = [
+            b"position",
+            config.key().as_ref(),
+            owner.key().as_ref(),
+            tick_lower.to_le_bytes().as_ref(),
+            tick_upper.to_le_bytes().as_ref()
+        ],
+        bump,
+    )]
+    pub position: Account<'info, Position>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> OpenPosition<'info> {
+    /// Opens an empty position over `[tick_lower, tick_upper)`. Liquidity is
+    /// added with `increase_liquidity`.
+    pub fn open_position(
+        &mut self,
+        tick_lower: i32,
+        tick_upper: i32,
+        bumps: &OpenPositionBumps,
+    ) -> Result<()> {
+        require!(tick_lower < tick_upper, AmmError::InvalidTick);
+        self.pool.check_tick(tick_lower)?;
+        self.pool.check_tick(tick_upper)?;
+
+        // Ticks are shared between positions and only set up by the first.
+        if self.lower_tick.config == Pubkey::default() {
+            self.lower_tick.config = self.config.key();
+            self.lower_tick.index = tick_lower;
+            self.lower_tick.bump = bumps.lower_tick;
+        }
+        if self.upper_tick.config == Pubkey::default() {
+            self.upper_tick.config = self.config.key();
+            self.upper_tick.index = tick_upper;
+            self.upper_tick.bump = bumps.upper_tick;
+        }
+
+        self.position.set_inner(Position {
+            config: self.config.key(),
+            owner: self.owner.key(),
+            tick_lower,
+            tick_upper,
+            liquidity: 0,
+            fee_growth_inside_x_last: 0,
+            fee_growth_inside_y_last: 0,
+            fees_owed_x: 0,
+            fees_owed_y: 0,
+            bump: bumps.position,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,165 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+use crate::{AmmError, Config, Farm, Stake, StakeEvent};
+
+#[derive(Accounts)]
+pub struct ModifyStake<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [
+            b"config",
+            config.seed.to_le_bytes().as_ref(),
+            config.mint_x.as_ref(),
+            config.mint_y.as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"farm", config.key().as_ref()],
+        bump = farm.bump,
+    )]
+    pub farm: Account<'info, Farm>,
+
+    #[account(
+        seeds = [b"lp", config.key().as_ref()],
+        bump = config.lp_mint_bump,
+    )]
+    pub mint_lp: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = Stake::INIT_SPACE,
+        seeds = [b"stake", farm.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub stake: Account<'info, Stake>,
+
+    #[account(
+        mut,
+        token::mint = mint_lp,
+        token::authority = owner,
+    )]
+    pub user_lp_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"farm_lp", farm.key().as_ref()],
+        bump = farm.stake_vault_bump,
+    )]
+    pub stake_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ModifyStake<'info> {
+    pub fn stake(&mut self, amount: u64, bumps: &ModifyStakeBumps) -> Result<()> {
+        require!(amount > 0, AmmError::InvalidAmount);
+        require!(!self.config.closing, AmmError::PoolFrozen);
+
+        let now = Clock::get()?.unix_timestamp;
+        self.farm.update(now)?;
+
+        // A fresh stake starts at the current reward per share.
+        if self.stake.owner == Pubkey::default() {
+            self.stake.set_inner(Stake {
+                farm: self.farm.key(),
+                owner: self.owner.key(),
+                amount: 0,
+                reward_per_share_last: self.farm.reward_per_share,
+                rewards_owed: 0,
+                bump: bumps.stake,
+            });
+        }
+
+        self.stake.accrue_rewards(self.farm.reward_per_share)?;
+        self.stake.amount = self
+            .stake
+            .amount
+            .checked_add(amount)
+            .ok_or(AmmError::InvalidAmount)?;
+        self.farm.total_staked = self
+            .farm
+            .total_staked
+            .checked_add(amount)
+            .ok_or(AmmError::InvalidAmount)?;
+
+        self.deposit_lp(amount)?;
+
+        self.emit_stake(true, amount, now);
+
+        Ok(())
+    }
+
+    /// Returns staked LP. Rewards earned so far stay claimable.
+    pub fn unstake(&mut self, amount: u64) -> Result<()> {
+        require!(
+            amount > 0 && amount <= self.stake.amount,
+            AmmError::InvalidAmount
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        self.farm.update(now)?;
+
+        self.stake.accrue_rewards(self.farm.reward_per_share)?;
+        self.stake.amount -= amount;
+        self.farm.total_staked -= amount;
+
+        self.withdraw_lp(amount)?;
+
+        self.emit_stake(false, amount, now);
+
+        Ok(())
+    }
+
+    fn deposit_lp(&self, amount: u64) -> Result<()> {
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_accounts = TransferChecked {
+            from: self.user_lp_ata.to_account_info(),
+            to: self.stake_vault.to_account_info(),
+            authority: self.owner.to_account_info(),
+            mint: self.mint_lp.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+        transfer_checked(cpi_ctx, amount, self.mint_lp.decimals)
+    }
+
+    fn withdraw_lp(&self, amount: u64) -> Result<()> {
+        let config = self.config.key();
+        let seeds = &[&b"farm"[..], config.as_ref(), &[self.farm.bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_accounts = TransferChecked {
+            from: self.stake_vault.to_account_info(),
+            to: self.user_lp_ata.to_account_info(),
+            authority: self.farm.to_account_info(),
+            mint: self.mint_lp.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        transfer_checked(cpi_ctx, amount, self.mint_lp.decimals)
+    }
+
+    fn emit_stake(&self, staked: bool, amount: u64, timestamp: i64) {
+        emit!(StakeEvent {
+            farm: self.farm.key(),
+            owner: self.owner.key(),
+            staked,
+            amount,
+            total_staked: self.farm.total_staked,
+            timestamp,
+        });
+    }
+}
@@ -1,11 +1,13 @@
+use crate::{
+    check_deadline,
+    token::{gross_amount, net_amount},
+    AmmError, Config, Oracle, SwapEvent, SwapQuote,
+};
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
     token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
 };
-use constant_product_curve::{ConstantProduct, LiquidityPair};
-
-use crate::{AmmError, Config};
 
 #[derive(Accounts)]
 pub struct Swap<'info> {
@@ -51,6 +53,7 @@ pub struct Swap<'info> {
 
     #[account(
         mut,
+        address = config.vault_x,
         token::mint = mint_x,
         token::authority = config,
     )]
@@ -58,17 +61,32 @@ pub struct Swap<'info> {
 
     #[account(
         mut,
+        address = config.vault_y,
         token::mint = mint_y,
         token::authority = config,
     )]
     vault_y: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
-        seeds = [b"auth"],
-        bump,
+        mut,
+        seeds = [b"fee_x", config.key().as_ref()],
+        bump = config.fee_vault_x_bump,
+    )]
+    fee_vault_x: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"fee_y", config.key().as_ref()],
+        bump = config.fee_vault_y_bump,
     )]
-    /// CHECK: safe to use unchecked account here
-    pub auth: UncheckedAccount<'info>,
+    fee_vault_y: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"oracle", config.key().as_ref()],
+        bump = config.oracle_bump,
+    )]
+    oracle: Account<'info, Oracle>,
 
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Interface<'info, TokenInterface>,
@@ -76,38 +94,157 @@ pub struct Swap<'info> {
 }
 
 impl<'info> Swap<'info> {
-    pub fn swap(&self, amount_in: u64, min_out: u64, expiration: i64, is_x: bool) -> Result<()> {
-        require!(self.config.locked == true, AmmError::Locked);
-        require!(expiration > Clock::get()?.unix_timestamp, AmmError::Expired);
+    pub fn swap(
+        &mut self,
+        amount_in: u64,
+        min_out: u64,
+        expiration: i64,
+        max_slot: Option<u64>,
+        is_x: bool,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+
+        self.config.check_unlocked()?;
+        check_deadline(&clock, expiration, max_slot)?;
         require!(amount_in > 0, AmmError::InvalidAmount);
 
-        let mut curve = ConstantProduct::init(
-            amount_in,
+        self.oracle.update(
+            self.vault_x.amount,
+            self.vault_y.amount,
+            clock.unix_timestamp,
+        );
+
+        // The user pays exactly `amount_in`; the curve prices what the vault
+        // receives once the input's transfer fee is withheld.
+        let mint_in = match is_x {
+            true => &self.mint_x,
+            false => &self.mint_y,
+        };
+        let net_in = net_amount(mint_in, amount_in)?;
+
+        let quote = self.config.quote_swap(
+            self.vault_x.amount,
+            self.vault_y.amount,
+            net_in,
             min_out,
-            self.mint_lp.supply,
-            self.config.fee,
-            Some(6),
-        )
-        .map_err(|_| AmmError::IncorrectK)?;
-
-        let p = match is_x {
-            true => LiquidityPair::X,
-            false => LiquidityPair::Y,
+            is_x,
+        )?;
+
+        // The curve checks `min_out` against what leaves the vault; the
+        // user also has to be covered for the output's transfer fee.
+        let mint_out = match is_x {
+            true => &self.mint_y,
+            false => &self.mint_x,
         };
+        require!(
+            net_amount(mint_out, quote.amount_out)? >= min_out,
+            AmmError::SlippageExceeded
+        );
+
+        // The protocol's share is moved out of the vault rather than paid
+        // separately, so the user is not charged a second transfer fee.
+        self.deposit_tokens(amount_in, is_x, false)?;
+        if quote.protocol_fee > 0 {
+            self.collect_protocol_fee(quote.protocol_fee, is_x)?;
+        }
+
+        self.settle(quote, is_x, clock.unix_timestamp)
+    }
+
+    /// Swaps for exactly `amount_out` of the other token, before its transfer
+    /// fee, paying at most `max_in` including the input's transfer fee.
+    pub fn swap_exact_out(
+        &mut self,
+        amount_out: u64,
+        max_in: u64,
+        expiration: i64,
+        max_slot: Option<u64>,
+        is_x: bool,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+
+        self.config.check_unlocked()?;
+        check_deadline(&clock, expiration, max_slot)?;
+        require!(amount_out > 0, AmmError::InvalidAmount);
+
+        self.oracle.update(
+            self.vault_x.amount,
+            self.vault_y.amount,
+            clock.unix_timestamp,
+        );
+
+        let quote = self.config.quote_swap_exact_out(
+            self.vault_x.amount,
+            self.vault_y.amount,
+            amount_out,
+            max_in,
+            is_x,
+        )?;
+
+        // Input transfer fees come on top of the quoted input.
+        let mint_in = match is_x {
+            true => &self.mint_x,
+            false => &self.mint_y,
+        };
+        let pool_in = gross_amount(mint_in, quote.pool_amount_in())?;
+        let protocol_fee_in = gross_amount(mint_in, quote.protocol_fee)?;
+        let paid = pool_in
+            .checked_add(protocol_fee_in)
+            .ok_or(AmmError::InvalidAmount)?;
+        require!(paid <= max_in, AmmError::SlippageExceeded);
+
+        self.deposit_tokens(pool_in, is_x, false)?;
+        if quote.protocol_fee > 0 {
+            self.deposit_tokens(protocol_fee_in, is_x, true)?;
+        }
+
+        self.settle(quote, is_x, clock.unix_timestamp)
+    }
+
+    /// Pays out a swap whose input has been deposited.
+    fn settle(&mut self, quote: SwapQuote, is_x: bool, timestamp: i64) -> Result<()> {
+        let fee_bps = self.config.total_fee()?;
+
+        self.withdraw_tokens(quote.amount_out, is_x)?;
 
-        let result = curve
-            .swap(p, amount_in, min_out)
-            .map_err(|_| AmmError::IncorrectK)?;
+        let (reserve_x, reserve_y) = quote
+            .reserves_after(self.vault_x.amount, self.vault_y.amount, is_x)
+            .ok_or(AmmError::IncorrectK)?;
+        self.config.record_price_move(
+            self.vault_x.amount,
+            self.vault_y.amount,
+            reserve_x,
+            reserve_y,
+            timestamp,
+        )?;
 
-        require!(result.deposit > 0, AmmError::InvalidDeposit);
-        require!(result.withdraw > 0, AmmError::InvalidWithdraw);
+        emit!(SwapEvent {
+            pool: self.config.key(),
+            user: self.user.key(),
+            is_x,
+            amount_in: quote.amount_in,
+            amount_out: quote.amount_out,
+            fee: quote.fee,
+            protocol_fee: quote.protocol_fee,
+            fee_bps,
+            price: quote.price().ok_or(AmmError::IncorrectK)?,
+            reserve_x,
+            reserve_y,
+            timestamp,
+        });
 
-        self.deposit_tokens(result.deposit, is_x)?;
-        self.withdraw_tokens(result.withdraw, is_x)
+        Ok(())
     }
 
     fn withdraw_tokens(&self, amount: u64, is_x: bool) -> Result<()> {
-        let seeds = &[&b"auth"[..], &[self.config.auth_bump]];
+        let seed = self.config.seed.to_le_bytes();
+        let seeds = &[
+            &b"config"[..],
+            seed.as_ref(),
+            self.config.mint_x.as_ref(),
+            self.config.mint_y.as_ref(),
+            &[self.config.config_bump],
+        ];
         let signer_seeds = &[&seeds[..]];
 
         let (from, to, mint, decimals) = match is_x {
@@ -129,7 +266,7 @@ impl<'info> Swap<'info> {
         let cpi_accounts = TransferChecked {
             from,
             to,
-            authority: self.user.to_account_info(),
+            authority: self.config.to_account_info(),
             mint,
         };
         let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
@@ -137,17 +274,23 @@ impl<'info> Swap<'info> {
         transfer_checked(cpi_context, amount, decimals)
     }
 
-    fn deposit_tokens(&self, amount: u64, is_x: bool) -> Result<()> {
+    fn deposit_tokens(&self, amount: u64, is_x: bool, to_fee_vault: bool) -> Result<()> {
         let (from, to, mint, decimals) = match is_x {
             true => (
                 self.user_x_ata.to_account_info(),
-                self.vault_x.to_account_info(),
+                match to_fee_vault {
+                    true => self.fee_vault_x.to_account_info(),
+                    false => self.vault_x.to_account_info(),
+                },
                 self.mint_x.to_account_info(),
                 self.mint_x.decimals,
             ),
             false => (
                 self.user_y_ata.to_account_info(),
-                self.vault_y.to_account_info(),
+                match to_fee_vault {
+                    true => self.fee_vault_y.to_account_info(),
+                    false => self.vault_y.to_account_info(),
+                },
                 self.mint_y.to_account_info(),
                 self.mint_y.decimals,
             ),
@@ -164,4 +307,44 @@ impl<'info> Swap<'info> {
 
         transfer_checked(cpi_context, amount, decimals)
     }
+
+    /// Moves `amount` of the input from the vault to the fee vault. With a
+    /// transfer fee, the fee vault bears it.
+    fn collect_protocol_fee(&self, amount: u64, is_x: bool) -> Result<()> {
+        let seed = self.config.seed.to_le_bytes();
+        let seeds = &[
+            &b"config"[..],
+            seed.as_ref(),
+            self.config.mint_x.as_ref(),
+            self.config.mint_y.as_ref(),
+            &[self.config.config_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let (from, to, mint, decimals) = match is_x {
+            true => (
+                self.vault_x.to_account_info(),
+                self.fee_vault_x.to_account_info(),
+                self.mint_x.to_account_info(),
+                self.mint_x.decimals,
+            ),
+            false => (
+                self.vault_y.to_account_info(),
+                self.fee_vault_y.to_account_info(),
+                self.mint_y.to_account_info(),
+                self.mint_y.decimals,
+            ),
+        };
+
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_accounts = TransferChecked {
+            from,
+            to,
+            authority: self.config.to_account_info(),
+            mint,
+        };
+        let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        transfer_checked(cpi_context, amount, decimals)
+    }
 }
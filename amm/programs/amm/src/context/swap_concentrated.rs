@@ -0,0 +1,324 @@
+use amm_math::{
+    add_liquidity_delta, fee_growth_delta, sqrt_price_at_tick, swap_step, tick_at_sqrt_price,
+    MAX_TICK, MIN_TICK,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+use crate::{
+    check_deadline,
+    token::{gross_amount, net_amount},
+    AmmError, ConcentratedPool, Config, SwapEvent, SwapQuote, Tick, TickBitmap,
+};
+
+#[derive(Accounts)]
+pub struct SwapConcentrated<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [
+            b"config",
+            config.seed.to_le_bytes().as_ref(),
+            mint_x.key().as_ref(),
+            mint_y.key().as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    pub mint_x: InterfaceAccount<'info, Mint>,
+    pub mint_y: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"concentrated", config.key().as_ref()],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, ConcentratedPool>,
+
+    #[account(
+        seeds = [b"bitmap", config.key().as_ref()],
+        bump = pool.bitmap_bump,
+    )]
+    pub bitmap: AccountLoader<'info, TickBitmap>,
+
+    #[account(
+        mut,
+        token::mint = mint_x,
+        token::authority = user,
+    )]
+    user_x_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = mint_y,
+        token::authority = user,
+    )]
+    user_y_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = config.vault_x,
+        token::mint = mint_x,
+        token::authority = config,
+    )]
+    vault_x: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = config.vault_y,
+        token::mint = mint_y,
+        token::authority = config,
+    )]
+    vault_y: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"fee_x", config.key().as_ref()],
+        bump = config.fee_vault_x_bump,
+    )]
+    fee_vault_x: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"fee_y", config.key().as_ref()],
+        bump = config.fee_vault_y_bump,
+    )]
+    fee_vault_y: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> SwapConcentrated<'info> {
+    /// Swaps up to `amount_in` through a concentrated pool. Every tick the
+    /// price crosses must be passed, writable and in crossing order, through
+    /// `remaining_accounts`. If the price runs into the end of the tick range
+    /// the swap stops there and only the input used is charged.
+    pub fn swap_concentrated(
+        &mut self,
+        remaining_accounts: &'info [AccountInfo<'info>],
+        amount_in: u64,
+        min_out: u64,
+        expiration: i64,
+        max_slot: Option<u64>,
+        is_x: bool,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+
+        self.config.check_unlocked()?;
+        check_deadline(&clock, expiration, max_slot)?;
+        require!(amount_in > 0, AmmError::InvalidAmount);
+
+        let config_key = self.config.key();
+        let fee = self.config.total_fee()?;
+        let bitmap = self.bitmap.load()?;
+        let pool = &mut self.pool;
+        let mut ticks = remaining_accounts.iter();
+
+        let mut remaining = amount_in;
+        let mut amount_out = 0u64;
+        let mut fee_total = 0u64;
+        let mut protocol_fee_total = 0u64;
+
+        while remaining > 0 {
+            let next_tick = bitmap.next_initialized(pool.tick_current, pool.tick_spacing, is_x);
+            let bound = match is_x {
+                true => MIN_TICK,
+                false => MAX_TICK,
+            };
+            let target =
+                sqrt_price_at_tick(next_tick.unwrap_or(bound)).ok_or(AmmError::InvalidTick)?;
+
+            let step = swap_step(
+                pool.sqrt_price,
+                target,
+                pool.liquidity,
+                remaining,
+                fee,
+                is_x,
+            )
+            .ok_or(AmmError::IncorrectK)?;
+
+            remaining = step
+                .amount_in
+                .checked_add(step.fee)
+                .and_then(|spent| remaining.checked_sub(spent))
+                .ok_or(AmmError::IncorrectK)?;
+            amount_out = amount_out
+                .checked_add(step.amount_out)
+                .ok_or(AmmError::IncorrectK)?;
+
+            let protocol_fee = self.config.protocol_fee_share(step.fee)?;
+            if let Some(growth) = fee_growth_delta(step.fee - protocol_fee, pool.liquidity) {
+                match is_x {
+                    true => {
+                        pool.fee_growth_global_x = pool.fee_growth_global_x.wrapping_add(growth)
+                    }
+                    false => {
+                        pool.fee_growth_global_y = pool.fee_growth_global_y.wrapping_add(growth)
+                    }
+                }
+            }
+            fee_total += step.fee;
+            protocol_fee_total += protocol_fee;
+
+            pool.sqrt_price = step.sqrt_price_next;
+
+            if step.sqrt_price_next != target {
+                pool.tick_current =
+                    tick_at_sqrt_price(step.sqrt_price_next).ok_or(AmmError::InvalidTick)?;
+                continue;
+            }
+
+            let index = match next_tick {
+                Some(index) => index,
+                None => break,
+            };
+
+            let mut tick = Account::<Tick>::try_from(ticks.next().ok_or(AmmError::InvalidTick)?)?;
+            require!(
+                tick.config == config_key && tick.index == index,
+                AmmError::InvalidTick
+            );
+
+            let liquidity_net = tick.cross(pool.fee_growth_global_x, pool.fee_growth_global_y);
+            let liquidity_delta = match is_x {
+                true => liquidity_net.checked_neg().ok_or(AmmError::IncorrectK)?,
+                false => liquidity_net,
+            };
+            pool.liquidity =
+                add_liquidity_delta(pool.liquidity, liquidity_delta).ok_or(AmmError::IncorrectK)?;
+            tick.exit(&crate::ID)?;
+
+            pool.tick_current = match is_x {
+                true => index - 1,
+                false => index,
+            };
+        }
+
+        drop(bitmap);
+
+        require!(amount_out > 0, AmmError::InvalidWithdraw);
+        let mint_out = match is_x {
+            true => &self.mint_y,
+            false => &self.mint_x,
+        };
+        require!(
+            net_amount(mint_out, amount_out)? >= min_out,
+            AmmError::SlippageExceeded
+        );
+
+        let quote = SwapQuote {
+            amount_in: amount_in - remaining,
+            amount_out,
+            fee: fee_total,
+            protocol_fee: protocol_fee_total,
+        };
+
+        self.deposit_tokens(quote.pool_amount_in(), is_x, false)?;
+        if quote.protocol_fee > 0 {
+            self.deposit_tokens(quote.protocol_fee, is_x, true)?;
+        }
+        self.withdraw_tokens(quote.amount_out, !is_x)?;
+
+        let (reserve_x, reserve_y) = quote
+            .reserves_after(self.vault_x.amount, self.vault_y.amount, is_x)
+            .ok_or(AmmError::IncorrectK)?;
+
+        emit!(SwapEvent {
+            pool: config_key,
+            user: self.user.key(),
+            is_x,
+            amount_in: quote.amount_in,
+            amount_out: quote.amount_out,
+            fee: quote.fee,
+            protocol_fee: quote.protocol_fee,
+            fee_bps: fee,
+            price: quote.price().ok_or(AmmError::IncorrectK)?,
+            reserve_x,
+            reserve_y,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    fn deposit_tokens(&self, amount: u64, is_x: bool, to_fee_vault: bool) -> Result<()> {
+        let (from, to, mint, decimals) = match is_x {
+            true => (
+                self.user_x_ata.to_account_info(),
+                match to_fee_vault {
+                    true => self.fee_vault_x.to_account_info(),
+                    false => self.vault_x.to_account_info(),
+                },
+                self.mint_x.to_account_info(),
+                self.mint_x.decimals,
+            ),
+            false => (
+                self.user_y_ata.to_account_info(),
+                match to_fee_vault {
+                    true => self.fee_vault_y.to_account_info(),
+                    false => self.vault_y.to_account_info(),
+                },
+                self.mint_y.to_account_info(),
+                self.mint_y.decimals,
+            ),
+        };
+
+        let amount = gross_amount(&mint, amount)?;
+
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_accounts = TransferChecked {
+            from,
+            to,
+            authority: self.user.to_account_info(),
+            mint,
+        };
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+        transfer_checked(cpi_ctx, amount, decimals)
+    }
+
+    fn withdraw_tokens(&self, amount: u64, is_x: bool) -> Result<()> {
+        let (from, to, mint, decimals) = match is_x {
+            true => (
+                self.vault_x.to_account_info(),
+                self.user_x_ata.to_account_info(),
+                self.mint_x.to_account_info(),
+                self.mint_x.decimals,
+            ),
+            false => (
+                self.vault_y.to_account_info(),
+                self.user_y_ata.to_account_info(),
+                self.mint_y.to_account_info(),
+                self.mint_y.decimals,
+            ),
+        };
+
+        let seed = self.config.seed.to_le_bytes();
+        let seeds = &[
+            &b"config"[..],
+            seed.as_ref(),
+            self.config.mint_x.as_ref(),
+            self.config.mint_y.as_ref(),
+            &[self.config.config_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_accounts = TransferChecked {
+            from,
+            to,
+            authority: self.config.to_account_info(),
+            mint,
+        };
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        transfer_checked(cpi_ctx, amount, decimals)
+    }
+}
@@ -0,0 +1,326 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+use crate::{
+    check_deadline,
+    token::{has_transfer_fee, net_amount},
+    AmmError, Config, Oracle, SwapEvent,
+};
+
+/// Accounts each hop of a route passes through `remaining_accounts`, in order:
+/// `config`, `mint_in`, `mint_out`, `vault_in`, `vault_out`, `fee_vault_in`
+/// and `oracle`. All but the mints must be writable.
+pub const ROUTE_HOP_ACCOUNTS: usize = 7;
+
+#[derive(Accounts)]
+pub struct SwapRoute<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        token::authority = user,
+    )]
+    user_in_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::authority = user,
+    )]
+    user_out_ata: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> SwapRoute<'info> {
+    /// Swaps `amount_in` through every pool in `remaining_accounts`, feeding
+    /// the output of each hop straight into the vault of the next one.
+    pub fn swap_route(
+        &self,
+        remaining_accounts: &'info [AccountInfo<'info>],
+        amount_in: u64,
+        min_out: u64,
+        expiration: i64,
+        max_slot: Option<u64>,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+
+        check_deadline(&clock, expiration, max_slot)?;
+        require!(amount_in > 0, AmmError::InvalidAmount);
+        require!(
+            !remaining_accounts.is_empty() && remaining_accounts.len() % ROUTE_HOP_ACCOUNTS == 0,
+            AmmError::InvalidRoute
+        );
+
+        let mut hops = remaining_accounts
+            .chunks(ROUTE_HOP_ACCOUNTS)
+            .map(RouteHop::load)
+            .collect::<Result<Vec<_>>>()?;
+
+        self.check_path(&hops)?;
+
+        // The user pays exactly `amount_in`; the first pool prices what its
+        // vault receives once the input's transfer fee is withheld.
+        let mut amount = net_amount(&hops[0].mint_in, amount_in)?;
+        let mut quotes = Vec::with_capacity(hops.len());
+        let mut fees_bps = Vec::with_capacity(hops.len());
+        for hop in hops.iter() {
+            let (x, y) = hop.reserves();
+            let quote = hop.config.quote_swap(x, y, amount, 1, hop.is_x)?;
+            amount = quote.amount_out;
+            quotes.push(quote);
+            fees_bps.push(hop.config.total_fee()?);
+        }
+
+        let last = &hops[hops.len() - 1];
+        require!(
+            net_amount(&last.mint_out, amount)? >= min_out,
+            AmmError::SlippageExceeded
+        );
+
+        for (hop, quote) in hops.iter_mut().zip(quotes.iter()) {
+            let (x, y) = hop.reserves();
+            hop.oracle.update(x, y, clock.unix_timestamp);
+            hop.oracle.exit(&crate::ID)?;
+
+            let (x_after, y_after) = quote
+                .reserves_after(x, y, hop.is_x)
+                .ok_or(AmmError::IncorrectK)?;
+            hop.config
+                .record_price_move(x, y, x_after, y_after, clock.unix_timestamp)?;
+            hop.config.exit(&crate::ID)?;
+        }
+
+        for (i, (hop, quote)) in hops.iter().zip(quotes.iter()).enumerate() {
+            match i {
+                0 => {
+                    self.pay_from_user(hop, amount_in)?;
+                    if quote.protocol_fee > 0 {
+                        self.collect_protocol_fee(hop, quote.protocol_fee)?;
+                    }
+                }
+                _ => {
+                    let previous = &hops[i - 1];
+                    self.pay_from_pool(
+                        previous,
+                        hop.vault_in.to_account_info(),
+                        quote.pool_amount_in(),
+                    )?;
+                    if quote.protocol_fee > 0 {
+                        self.pay_from_pool(
+                            previous,
+                            hop.fee_vault_in.to_account_info(),
+                            quote.protocol_fee,
+                        )?;
+                    }
+                }
+            }
+
+            let (x, y) = hop.reserves();
+            let (reserve_x, reserve_y) = quote
+                .reserves_after(x, y, hop.is_x)
+                .ok_or(AmmError::IncorrectK)?;
+
+            emit!(SwapEvent {
+                pool: hop.config.key(),
+                user: self.user.key(),
+                is_x: hop.is_x,
+                amount_in: quote.amount_in,
+                amount_out: quote.amount_out,
+                fee: quote.fee,
+                protocol_fee: quote.protocol_fee,
+                fee_bps: fees_bps[i],
+                price: quote.price().ok_or(AmmError::IncorrectK)?,
+                reserve_x,
+                reserve_y,
+                timestamp: clock.unix_timestamp,
+            });
+        }
+
+        let last = &hops[hops.len() - 1];
+        self.pay_from_pool(last, self.user_out_ata.to_account_info(), amount)
+    }
+
+    fn check_path(&self, hops: &[RouteHop<'info>]) -> Result<()> {
+        require_keys_eq!(
+            hops[0].mint_in.key(),
+            self.user_in_ata.mint,
+            AmmError::InvalidRoute
+        );
+        require_keys_eq!(
+            hops[hops.len() - 1].mint_out.key(),
+            self.user_out_ata.mint,
+            AmmError::InvalidRoute
+        );
+
+        for (i, hop) in hops.iter().enumerate() {
+            hop.config.check_unlocked()?;
+
+            if i > 0 {
+                require_keys_eq!(
+                    hop.mint_in.key(),
+                    hops[i - 1].mint_out.key(),
+                    AmmError::InvalidRoute
+                );
+
+                // Intermediate tokens move vault to vault in two transfers,
+                // so a transfer fee could not be charged to the user.
+                require!(!has_transfer_fee(&hop.mint_in)?, AmmError::InvalidRoute);
+            }
+
+            // Quotes are taken up front, so a pool may only appear once.
+            require!(
+                hops[..i].iter().all(|h| h.config.key() != hop.config.key()),
+                AmmError::InvalidRoute
+            );
+        }
+
+        Ok(())
+    }
+
+    fn pay_from_user(&self, hop: &RouteHop<'info>, amount: u64) -> Result<()> {
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_accounts = TransferChecked {
+            from: self.user_in_ata.to_account_info(),
+            to: hop.vault_in.to_account_info(),
+            authority: self.user.to_account_info(),
+            mint: hop.mint_in.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+        transfer_checked(cpi_ctx, amount, hop.mint_in.decimals)
+    }
+
+    /// Moves the first pool's protocol fee out of its vault, so the user is
+    /// not charged a second transfer fee. With a transfer fee, the fee vault
+    /// bears it.
+    fn collect_protocol_fee(&self, hop: &RouteHop<'info>, amount: u64) -> Result<()> {
+        let seed = hop.config.seed.to_le_bytes();
+        let seeds = &[
+            &b"config"[..],
+            seed.as_ref(),
+            hop.config.mint_x.as_ref(),
+            hop.config.mint_y.as_ref(),
+            &[hop.config.config_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_accounts = TransferChecked {
+            from: hop.vault_in.to_account_info(),
+            to: hop.fee_vault_in.to_account_info(),
+            authority: hop.config.to_account_info(),
+            mint: hop.mint_in.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        transfer_checked(cpi_ctx, amount, hop.mint_in.decimals)
+    }
+
+    fn pay_from_pool(
+        &self,
+        hop: &RouteHop<'info>,
+        to: AccountInfo<'info>,
+        amount: u64,
+    ) -> Result<()> {
+        let seed = hop.config.seed.to_le_bytes();
+        let seeds = &[
+            &b"config"[..],
+            seed.as_ref(),
+            hop.config.mint_x.as_ref(),
+            hop.config.mint_y.as_ref(),
+            &[hop.config.config_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_accounts = TransferChecked {
+            from: hop.vault_out.to_account_info(),
+            to,
+            authority: hop.config.to_account_info(),
+            mint: hop.mint_out.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        transfer_checked(cpi_ctx, amount, hop.mint_out.decimals)
+    }
+}
+
+struct RouteHop<'info> {
+    config: Account<'info, Config>,
+    mint_in: InterfaceAccount<'info, Mint>,
+    mint_out: InterfaceAccount<'info, Mint>,
+    vault_in: InterfaceAccount<'info, TokenAccount>,
+    vault_out: InterfaceAccount<'info, TokenAccount>,
+    fee_vault_in: InterfaceAccount<'info, TokenAccount>,
+    oracle: Account<'info, Oracle>,
+    is_x: bool,
+}
+
+impl<'info> RouteHop<'info> {
+    fn load(accounts: &'info [AccountInfo<'info>]) -> Result<Self> {
+        let config = Account::<Config>::try_from(&accounts[0])?;
+        let mint_in = InterfaceAccount::<Mint>::try_from(&accounts[1])?;
+        let mint_out = InterfaceAccount::<Mint>::try_from(&accounts[2])?;
+        let vault_in = InterfaceAccount::<TokenAccount>::try_from(&accounts[3])?;
+        let vault_out = InterfaceAccount::<TokenAccount>::try_from(&accounts[4])?;
+        let fee_vault_in = InterfaceAccount::<TokenAccount>::try_from(&accounts[5])?;
+        let oracle = Account::<Oracle>::try_from(&accounts[6])?;
+
+        let is_x = match (mint_in.key(), mint_out.key()) {
+            (i, o) if i == config.mint_x && o == config.mint_y => true,
+            (i, o) if i == config.mint_y && o == config.mint_x => false,
+            _ => return err!(AmmError::InvalidRoute),
+        };
+
+        let config_key = config.key();
+        let fee_vault_x = pool_address(b"fee_x", &config_key, config.fee_vault_x_bump)?;
+        let fee_vault_y = pool_address(b"fee_y", &config_key, config.fee_vault_y_bump)?;
+        let fee_vault_in_key = match is_x {
+            true => fee_vault_x,
+            false => fee_vault_y,
+        };
+
+        require_keys_eq!(fee_vault_in.key(), fee_vault_in_key, AmmError::InvalidRoute);
+        require_keys_eq!(
+            oracle.key(),
+            pool_address(b"oracle", &config_key, config.oracle_bump)?,
+            AmmError::InvalidRoute
+        );
+
+        let (vault_in_key, vault_out_key) = match is_x {
+            true => (config.vault_x, config.vault_y),
+            false => (config.vault_y, config.vault_x),
+        };
+
+        require_keys_eq!(vault_in.key(), vault_in_key, AmmError::InvalidRoute);
+        require_keys_eq!(vault_out.key(), vault_out_key, AmmError::InvalidRoute);
+
+        Ok(Self {
+            config,
+            mint_in,
+            mint_out,
+            vault_in,
+            vault_out,
+            fee_vault_in,
+            oracle,
+            is_x,
+        })
+    }
+
+    fn reserves(&self) -> (u64, u64) {
+        match self.is_x {
+            true => (self.vault_in.amount, self.vault_out.amount),
+            false => (self.vault_out.amount, self.vault_in.amount),
+        }
+    }
+}
+
+fn pool_address(prefix: &[u8], config: &Pubkey, bump: u8) -> Result<Pubkey> {
+    let address = Pubkey::create_program_address(&[prefix, config.as_ref(), &[bump]], &crate::ID)
+        .map_err(|_| AmmError::InvalidRoute)?;
+
+    Ok(address)
+}
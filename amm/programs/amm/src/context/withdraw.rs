@@ -1,3 +1,4 @@
+use amm_math::quote_withdraw;
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
@@ -5,9 +6,8 @@ use anchor_spl::{
         burn, transfer_checked, Burn, Mint, TokenAccount, TokenInterface, TransferChecked,
     },
 };
-use constant_product_curve::ConstantProduct;
 
-use crate::{AmmError, Config};
+use crate::{check_deadline, token::net_amount, AmmError, Config, Oracle, WithdrawEvent};
 
 #[derive(Accounts)]
 pub struct Withdaw<'info> {
@@ -61,6 +61,7 @@ pub struct Withdaw<'info> {
 
     #[account(
         mut,
+        address = config.vault_x,
         token::mint = mint_x,
         token::authority = config,
     )]
@@ -68,17 +69,18 @@ pub struct Withdaw<'info> {
 
     #[account(
         mut,
+        address = config.vault_y,
         token::mint = mint_y,
         token::authority = config,
     )]
     vault_y: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
-        seeds = [b"auth"],
-        bump,
+        mut,
+        seeds = [b"oracle", config.key().as_ref()],
+        bump = config.oracle_bump,
     )]
-    /// CHECK: safe to use unchecked account here
-    pub auth: UncheckedAccount<'info>,
+    oracle: Account<'info, Oracle>,
 
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Interface<'info, TokenInterface>,
@@ -86,31 +88,61 @@ pub struct Withdaw<'info> {
 }
 
 impl<'info> Withdaw<'info> {
-    pub fn withdraw(&mut self, amount: u64, min_x: u64, min_y: u64, expiration: i64) -> Result<()> {
-        require!(self.config.locked == true, AmmError::Locked);
-        require!(expiration > Clock::get()?.unix_timestamp, AmmError::Expired);
+    pub fn withdraw(
+        &mut self,
+        amount: u64,
+        min_x: u64,
+        min_y: u64,
+        expiration: i64,
+        max_slot: Option<u64>,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+
+        self.config.check_withdrawable()?;
+        check_deadline(&clock, expiration, max_slot)?;
         require!(
             amount > 0 && min_x > 0 && min_y > 0,
             AmmError::InvalidAmount
         );
 
-        let amounts = ConstantProduct::xy_withdraw_amounts_from_l(
+        self.oracle.update(
+            self.vault_x.amount,
+            self.vault_y.amount,
+            clock.unix_timestamp,
+        );
+
+        // Proportional withdrawals leave the price unchanged on either curve.
+        let (amount_x, amount_y) = quote_withdraw(
             self.vault_x.amount,
             self.vault_y.amount,
             self.mint_lp.supply,
             amount,
-            6,
         )
-        .map_err(|_| AmmError::InvalidWithdraw)?;
+        .ok_or(AmmError::InvalidWithdraw)?;
 
         require!(
-            amounts.x <= min_x && amounts.y <= min_y,
+            net_amount(&self.mint_x, amount_x)? >= min_x
+                && net_amount(&self.mint_y, amount_y)? >= min_y,
             AmmError::SlippageExceeded
         );
 
-        self.withdraw_tokens(amount, true)?;
-        self.withdraw_tokens(amount, false)?;
-        self.burn_lp_tokens(amount)
+        self.withdraw_tokens(amount_x, true)?;
+        self.withdraw_tokens(amount_y, false)?;
+        self.burn_lp_tokens(amount)?;
+
+        emit!(WithdrawEvent {
+            pool: self.config.key(),
+            user: self.user.key(),
+            amount_x,
+            amount_y,
+            lp_burned: amount,
+            reserve_x: self.vault_x.amount - amount_x,
+            reserve_y: self.vault_y.amount - amount_y,
+            lp_supply: self.mint_lp.supply - amount,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
     }
 
     fn burn_lp_tokens(&self, amount: u64) -> Result<()> {
@@ -141,14 +173,21 @@ impl<'info> Withdaw<'info> {
             ),
         };
 
-        let seeds = &[&b"auth"[..], &[self.config.auth_bump]];
+        let seed = self.config.seed.to_le_bytes();
+        let seeds = &[
+            &b"config"[..],
+            seed.as_ref(),
+            self.config.mint_x.as_ref(),
+            self.config.mint_y.as_ref(),
+            &[self.config.config_bump],
+        ];
         let signer_seeds = &[&seeds[..]];
 
         let cpi_program = self.token_program.to_account_info();
         let cpi_accounts = TransferChecked {
             from,
             to,
-            authority: self.auth.to_account_info(),
+            authority: self.config.to_account_info(),
             mint,
         };
         let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
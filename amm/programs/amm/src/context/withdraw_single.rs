@@ -0,0 +1,267 @@
+use amm_math::quote_withdraw;
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        burn, transfer_checked, Burn, Mint, TokenAccount, TokenInterface, TransferChecked,
+    },
+};
+
+use crate::{
+    check_deadline, token::net_amount, AmmError, Config, Oracle, SwapEvent, WithdrawEvent,
+};
+
+#[derive(Accounts)]
+pub struct WithdrawSingle<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"config",
+            config.seed.to_le_bytes().as_ref(),
+            mint_x.key().as_ref(),
+            mint_y.key().as_ref()
+        ],
+        bump = config.config_bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"lp", config.key().as_ref()],
+        bump = config.lp_mint_bump,
+    )]
+    pub mint_lp: InterfaceAccount<'info, Mint>,
+
+    pub mint_x: InterfaceAccount<'info, Mint>,
+    pub mint_y: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_x,
+        associated_token::authority = user,
+    )]
+    user_x_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_y,
+        associated_token::authority = user,
+    )]
+    user_y_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = mint_lp,
+        token::authority = user,
+    )]
+    user_lp_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = config.vault_x,
+        token::mint = mint_x,
+        token::authority = config,
+    )]
+    vault_x: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = config.vault_y,
+        token::mint = mint_y,
+        token::authority = config,
+    )]
+    vault_y: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"fee_x", config.key().as_ref()],
+        bump = config.fee_vault_x_bump,
+    )]
+    fee_vault_x: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"fee_y", config.key().as_ref()],
+        bump = config.fee_vault_y_bump,
+    )]
+    fee_vault_y: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"oracle", config.key().as_ref()],
+        bump = config.oracle_bump,
+    )]
+    oracle: Account<'info, Oracle>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> WithdrawSingle<'info> {
+    /// Burns `amount` LP and pays out only X (`is_x`) or only Y. The share of
+    /// the other side is swapped into the chosen one at the pool price, fee
+    /// included, after the pro-rata amounts have been removed.
+    pub fn withdraw_single(
+        &mut self,
+        amount: u64,
+        min_out: u64,
+        expiration: i64,
+        max_slot: Option<u64>,
+        is_x: bool,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+
+        self.config.check_unlocked()?;
+        check_deadline(&clock, expiration, max_slot)?;
+        require!(amount > 0 && min_out > 0, AmmError::InvalidAmount);
+
+        self.oracle.update(
+            self.vault_x.amount,
+            self.vault_y.amount,
+            clock.unix_timestamp,
+        );
+
+        let (amount_x, amount_y) = quote_withdraw(
+            self.vault_x.amount,
+            self.vault_y.amount,
+            self.mint_lp.supply,
+            amount,
+        )
+        .ok_or(AmmError::InvalidWithdraw)?;
+
+        let x = self
+            .vault_x
+            .amount
+            .checked_sub(amount_x)
+            .ok_or(AmmError::InvalidWithdraw)?;
+        let y = self
+            .vault_y
+            .amount
+            .checked_sub(amount_y)
+            .ok_or(AmmError::InvalidWithdraw)?;
+
+        // Swap the unwanted side back into the pool.
+        let (kept, swapped) = match is_x {
+            true => (amount_x, amount_y),
+            false => (amount_y, amount_x),
+        };
+
+        let quote = self.config.quote_swap(x, y, swapped, 1, !is_x)?;
+        let (reserve_x, reserve_y) = quote
+            .reserves_after(x, y, !is_x)
+            .ok_or(AmmError::IncorrectK)?;
+
+        // Selling the other side back to the pool moves its price.
+        self.config
+            .record_price_move(x, y, reserve_x, reserve_y, clock.unix_timestamp)?;
+
+        let out = kept
+            .checked_add(quote.amount_out)
+            .ok_or(AmmError::InvalidWithdraw)?;
+        let mint_out = match is_x {
+            true => &self.mint_x,
+            false => &self.mint_y,
+        };
+        require!(
+            net_amount(mint_out, out)? >= min_out,
+            AmmError::SlippageExceeded
+        );
+
+        self.withdraw_tokens(out, is_x, false)?;
+        if quote.protocol_fee > 0 {
+            self.withdraw_tokens(quote.protocol_fee, !is_x, true)?;
+        }
+        self.burn_lp_tokens(amount)?;
+
+        emit!(WithdrawEvent {
+            pool: self.config.key(),
+            user: self.user.key(),
+            amount_x,
+            amount_y,
+            lp_burned: amount,
+            reserve_x: x,
+            reserve_y: y,
+            lp_supply: self.mint_lp.supply - amount,
+            timestamp: clock.unix_timestamp,
+        });
+
+        emit!(SwapEvent {
+            pool: self.config.key(),
+            user: self.user.key(),
+            is_x: !is_x,
+            amount_in: quote.amount_in,
+            amount_out: quote.amount_out,
+            fee: quote.fee,
+            protocol_fee: quote.protocol_fee,
+            fee_bps: self.config.total_fee()?,
+            price: quote.price().ok_or(AmmError::IncorrectK)?,
+            reserve_x,
+            reserve_y,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    fn burn_lp_tokens(&self, amount: u64) -> Result<()> {
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_accounts = Burn {
+            mint: self.mint_lp.to_account_info(),
+            from: self.user_lp_ata.to_account_info(),
+            authority: self.user.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+        burn(cpi_ctx, amount)
+    }
+
+    fn withdraw_tokens(&self, amount: u64, is_x: bool, to_fee_vault: bool) -> Result<()> {
+        let (from, to, mint, decimals) = match is_x {
+            true => (
+                self.vault_x.to_account_info(),
+                match to_fee_vault {
+                    true => self.fee_vault_x.to_account_info(),
+                    false => self.user_x_ata.to_account_info(),
+                },
+                self.mint_x.to_account_info(),
+                self.mint_x.decimals,
+            ),
+            false => (
+                self.vault_y.to_account_info(),
+                match to_fee_vault {
+                    true => self.fee_vault_y.to_account_info(),
+                    false => self.user_y_ata.to_account_info(),
+                },
+                self.mint_y.to_account_info(),
+                self.mint_y.decimals,
+            ),
+        };
+
+        let seed = self.config.seed.to_le_bytes();
+        let seeds = &[
+            &b"config"[..],
+            seed.as_ref(),
+            self.config.mint_x.as_ref(),
+            self.config.mint_y.as_ref(),
+            &[self.config.config_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_accounts = TransferChecked {
+            from,
+            to,
+            authority: self.config.to_account_info(),
+            mint,
+        };
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        transfer_checked(cpi_ctx, amount, decimals)
+    }
+}
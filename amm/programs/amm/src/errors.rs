@@ -2,7 +2,7 @@ use anchor_lang::error_code;
 
 #[error_code]
 pub enum AmmError {
-    #[msg("The fee must be less than 100% (< 10000)")]
+    #[msg("The LP and protocol fees combined must be less than 100% (< 10000)")]
     FeeTooHigh,
 
     #[msg("The liquidity pool is locked. You cannot deposit or withdraw from it.")]
@@ -25,4 +25,70 @@ pub enum AmmError {
 
     #[msg("Incorrect K. Please try again.")]
     IncorrectK,
+
+    #[msg("The LP mint authority has already been migrated to the pool auth.")]
+    AlreadyMigrated,
+
+    #[msg("The signer is not the pending authority of this pool.")]
+    InvalidAuthority,
+
+    #[msg("The oracle has no observation old enough for the requested window.")]
+    InsufficientObservations,
+
+    #[msg("The swap route accounts are invalid or do not form a path.")]
+    InvalidRoute,
+
+    #[msg("The amplification coefficient or its ramp is out of bounds.")]
+    InvalidAmp,
+
+    #[msg("The operation is not supported by this pool's curve.")]
+    InvalidCurve,
+
+    #[msg("The tick is out of range, misaligned or not the one expected.")]
+    InvalidTick,
+
+    #[msg("The position still holds liquidity or uncollected fees.")]
+    PositionNotEmpty,
+
+    #[msg("A flash loan is outstanding on this pool.")]
+    FlashLoanActive,
+
+    #[msg("The flash loan must be a top-level instruction followed by its flash_repay.")]
+    InvalidFlashLoan,
+
+    #[msg("The mint uses a Token-2022 extension pools do not support, such as a transfer hook or non-transferability.")]
+    UnsupportedMint,
+
+    #[msg("Pool mints must be ordered so that mint X sorts before mint Y.")]
+    InvalidMintOrder,
+
+    #[msg("The pair index already lists the maximum number of pools.")]
+    PairIndexFull,
+
+    #[msg("The minimum dynamic fee must not exceed the maximum.")]
+    InvalidFeeBounds,
+
+    #[msg("The slot deadline has passed.")]
+    SlotDeadlineExceeded,
+
+    #[msg("The trade exceeds the pool's maximum share of its reserves.")]
+    TradeTooLarge,
+
+    #[msg("LP is outstanding that the final distribution does not cover.")]
+    PoolNotEmpty,
+
+    #[msg("The reward schedule is too short or does not stream any rewards.")]
+    InvalidRewardSchedule,
+
+    #[msg("LP is still staked in the pool's farm.")]
+    FarmNotEmpty,
+
+    #[msg("The pool is frozen for closing.")]
+    PoolFrozen,
+
+    #[msg("The pool must be frozen before it is closed.")]
+    PoolNotFrozen,
+
+    #[msg("An LP holder's accounts do not match the pool or each other.")]
+    HolderMismatch,
 }
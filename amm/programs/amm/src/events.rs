@@ -0,0 +1,231 @@
+use anchor_lang::prelude::*;
+
+use crate::CurveType;
+
+#[event]
+pub struct InitializeEvent {
+    pub pool: Pubkey,
+    pub authority: Pubkey,
+    pub mint_x: Pubkey,
+    pub mint_y: Pubkey,
+    pub mint_lp: Pubkey,
+    pub curve: CurveType,
+    pub fee: u16,
+    pub protocol_fee: u16,
+    pub canonical: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DepositEvent {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub amount_x: u64,
+    pub amount_y: u64,
+    pub lp_minted: u64,
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+    /// LP supply after the deposit, including any `MINIMUM_LIQUIDITY` locked.
+    pub lp_supply: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WithdrawEvent {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub amount_x: u64,
+    pub amount_y: u64,
+    pub lp_burned: u64,
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+    pub lp_supply: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SwapEvent {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub is_x: bool,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee: u64,
+    /// Part of `fee` routed to the protocol fee vault.
+    pub protocol_fee: u64,
+    /// Fee rate charged, LP and protocol combined, in bps.
+    pub fee_bps: u16,
+    /// Realized price of the input token in the output token, scaled by `PRICE_PRECISION`.
+    pub price: u64,
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FlashLoanEvent {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    /// Whether the loan was repaid rather than taken.
+    pub repaid: bool,
+    pub is_x: bool,
+    pub amount: u64,
+    /// Fee owed on top of `amount`, kept by LPs.
+    pub fee: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PositionLiquidityEvent {
+    pub pool: Pubkey,
+    pub position: Pubkey,
+    pub owner: Pubkey,
+    /// Whether liquidity was added rather than removed.
+    pub increased: bool,
+    pub liquidity_delta: u128,
+    pub amount_x: u64,
+    pub amount_y: u64,
+    /// Position liquidity afterwards.
+    pub liquidity: u128,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PositionFeesCollectedEvent {
+    pub pool: Pubkey,
+    pub position: Pubkey,
+    pub owner: Pubkey,
+    pub amount_x: u64,
+    pub amount_y: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProtocolFeesCollectedEvent {
+    pub pool: Pubkey,
+    pub authority: Pubkey,
+    pub amount_x: u64,
+    pub amount_y: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FeeUpdatedEvent {
+    pub pool: Pubkey,
+    pub authority: Pubkey,
+    pub old_fee: u16,
+    pub new_fee: u16,
+    pub old_protocol_fee: u16,
+    pub new_protocol_fee: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DynamicFeeEvent {
+    pub pool: Pubkey,
+    pub authority: Pubkey,
+    pub enabled: bool,
+    pub min_fee: u16,
+    pub max_fee: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MaxTradeUpdatedEvent {
+    pub pool: Pubkey,
+    pub authority: Pubkey,
+    pub old_max_trade_bps: u16,
+    pub new_max_trade_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FlashLoanFeeUpdatedEvent {
+    pub pool: Pubkey,
+    pub authority: Pubkey,
+    pub old_flash_loan_fee: u16,
+    pub new_flash_loan_fee: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LockEvent {
+    pub pool: Pubkey,
+    pub authority: Pubkey,
+    pub locked: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AuthorityProposedEvent {
+    pub pool: Pubkey,
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AuthorityAcceptedEvent {
+    pub pool: Pubkey,
+    pub old_authority: Pubkey,
+    pub new_authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AmpRampEvent {
+    pub pool: Pubkey,
+    pub authority: Pubkey,
+    pub initial_amp: u64,
+    pub target_amp: u64,
+    pub ramp_start_ts: i64,
+    pub ramp_stop_ts: i64,
+}
+
+#[event]
+pub struct RewardsFundedEvent {
+    pub farm: Pubkey,
+    pub authority: Pubkey,
+    pub amount: u64,
+    pub reward_rate: u64,
+    pub reward_end_ts: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StakeEvent {
+    pub farm: Pubkey,
+    pub owner: Pubkey,
+    /// Whether LP was staked rather than unstaked.
+    pub staked: bool,
+    pub amount: u64,
+    pub total_staked: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardsClaimedEvent {
+    pub farm: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PoolFrozenEvent {
+    pub pool: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PoolClosedEvent {
+    pub pool: Pubkey,
+    pub authority: Pubkey,
+    /// Outstanding LP settled by the final distribution.
+    pub lp_settled: u64,
+    /// Reserves paid out to LP holders.
+    pub amount_x: u64,
+    pub amount_y: u64,
+    pub timestamp: i64,
+}
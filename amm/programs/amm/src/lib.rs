@@ -4,17 +4,288 @@ declare_id!("AjTrriDodhzonGJ3mCp2WknCaYNV58eXKiewD3YyhKjo");
 
 pub mod context;
 pub mod errors;
+pub mod events;
 pub mod state;
+pub mod token;
 
 pub use context::*;
 pub use errors::*;
+pub use events::*;
 pub use state::*;
 
 #[program]
 pub mod amm {
     use super::*;
 
-    pub fn initialize_amm(ctx: Context<Init>, seed: u64, fee: u16) -> Result<()> {
-        ctx.accounts.init(seed, fee, &ctx.bumps)
+    pub fn initialize_amm(
+        ctx: Context<Init>,
+        seed: u64,
+        fee: u16,
+        protocol_fee: u16,
+        curve: CurveType,
+        amp: u64,
+        flash_loan_fee: u16,
+    ) -> Result<()> {
+        let args = InitArgs {
+            seed,
+            fee,
+            protocol_fee,
+            curve,
+            amp,
+            flash_loan_fee,
+        };
+
+        ctx.accounts.init(args, &ctx.bumps)
+    }
+
+    pub fn deposit(
+        ctx: Context<Deposit>,
+        amount: u64,
+        max_x: u64,
+        max_y: u64,
+        expiration: i64,
+        max_slot: Option<u64>,
+    ) -> Result<()> {
+        ctx.accounts
+            .deposit(amount, max_x, max_y, expiration, max_slot)
+    }
+
+    pub fn deposit_single(
+        ctx: Context<DepositSingle>,
+        amount_in: u64,
+        min_lp: u64,
+        expiration: i64,
+        max_slot: Option<u64>,
+        is_x: bool,
+    ) -> Result<()> {
+        ctx.accounts
+            .deposit_single(amount_in, min_lp, expiration, max_slot, is_x)
+    }
+
+    pub fn withdraw(
+        ctx: Context<Withdaw>,
+        amount: u64,
+        min_x: u64,
+        min_y: u64,
+        expiration: i64,
+        max_slot: Option<u64>,
+    ) -> Result<()> {
+        ctx.accounts
+            .withdraw(amount, min_x, min_y, expiration, max_slot)
+    }
+
+    pub fn withdraw_single(
+        ctx: Context<WithdrawSingle>,
+        amount: u64,
+        min_out: u64,
+        expiration: i64,
+        max_slot: Option<u64>,
+        is_x: bool,
+    ) -> Result<()> {
+        ctx.accounts
+            .withdraw_single(amount, min_out, expiration, max_slot, is_x)
+    }
+
+    pub fn swap(
+        ctx: Context<Swap>,
+        amount_in: u64,
+        min_out: u64,
+        expiration: i64,
+        max_slot: Option<u64>,
+        is_x: bool,
+    ) -> Result<()> {
+        ctx.accounts
+            .swap(amount_in, min_out, expiration, max_slot, is_x)
+    }
+
+    pub fn migrate_auth(ctx: Context<MigrateAuth>) -> Result<()> {
+        ctx.accounts.migrate_auth(&ctx.bumps)
+    }
+
+    pub fn swap_exact_out(
+        ctx: Context<Swap>,
+        amount_out: u64,
+        max_in: u64,
+        expiration: i64,
+        max_slot: Option<u64>,
+        is_x: bool,
+    ) -> Result<()> {
+        ctx.accounts
+            .swap_exact_out(amount_out, max_in, expiration, max_slot, is_x)
+    }
+
+    pub fn swap_route<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SwapRoute<'info>>,
+        amount_in: u64,
+        min_out: u64,
+        expiration: i64,
+        max_slot: Option<u64>,
+    ) -> Result<()> {
+        ctx.accounts.swap_route(
+            ctx.remaining_accounts,
+            amount_in,
+            min_out,
+            expiration,
+            max_slot,
+        )
+    }
+
+    pub fn initialize_concentrated(
+        ctx: Context<InitConcentrated>,
+        tick_spacing: u16,
+        sqrt_price: u128,
+    ) -> Result<()> {
+        ctx.accounts
+            .init_concentrated(tick_spacing, sqrt_price, &ctx.bumps)
+    }
+
+    pub fn open_position(
+        ctx: Context<OpenPosition>,
+        tick_lower: i32,
+        tick_upper: i32,
+    ) -> Result<()> {
+        ctx.accounts
+            .open_position(tick_lower, tick_upper, &ctx.bumps)
+    }
+
+    pub fn increase_liquidity(
+        ctx: Context<ModifyPosition>,
+        liquidity: u128,
+        max_x: u64,
+        max_y: u64,
+        expiration: i64,
+        max_slot: Option<u64>,
+    ) -> Result<()> {
+        ctx.accounts
+            .increase_liquidity(liquidity, max_x, max_y, expiration, max_slot)
+    }
+
+    pub fn decrease_liquidity(
+        ctx: Context<ModifyPosition>,
+        liquidity: u128,
+        min_x: u64,
+        min_y: u64,
+        expiration: i64,
+        max_slot: Option<u64>,
+    ) -> Result<()> {
+        ctx.accounts
+            .decrease_liquidity(liquidity, min_x, min_y, expiration, max_slot)
+    }
+
+    pub fn collect_fees(ctx: Context<ModifyPosition>) -> Result<()> {
+        ctx.accounts.collect_fees()
+    }
+
+    /// The position is closed by the account constraints once it is empty.
+    pub fn close_position(_ctx: Context<ClosePosition>) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn swap_concentrated<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SwapConcentrated<'info>>,
+        amount_in: u64,
+        min_out: u64,
+        expiration: i64,
+        max_slot: Option<u64>,
+        is_x: bool,
+    ) -> Result<()> {
+        ctx.accounts.swap_concentrated(
+            ctx.remaining_accounts,
+            amount_in,
+            min_out,
+            expiration,
+            max_slot,
+            is_x,
+        )
+    }
+
+    pub fn observe(ctx: Context<Observe>, seconds_ago: u32) -> Result<Twap> {
+        ctx.accounts.observe(seconds_ago)
+    }
+
+    pub fn collect_protocol_fees(ctx: Context<CollectProtocolFees>) -> Result<()> {
+        ctx.accounts.collect_protocol_fees()
+    }
+
+    pub fn lock(ctx: Context<Admin>) -> Result<()> {
+        ctx.accounts.lock()
+    }
+
+    pub fn unlock(ctx: Context<Admin>) -> Result<()> {
+        ctx.accounts.unlock()
+    }
+
+    pub fn update_fee(ctx: Context<Admin>, fee: u16, protocol_fee: u16) -> Result<()> {
+        ctx.accounts.update_fee(fee, protocol_fee)
+    }
+
+    pub fn ramp_amp(ctx: Context<Admin>, target_amp: u64, stop_ts: i64) -> Result<()> {
+        ctx.accounts.ramp_amp(target_amp, stop_ts)
+    }
+
+    pub fn stop_ramp_amp(ctx: Context<Admin>) -> Result<()> {
+        ctx.accounts.stop_ramp_amp()
+    }
+
+    pub fn set_dynamic_fee(
+        ctx: Context<Admin>,
+        enabled: bool,
+        min_fee: u16,
+        max_fee: u16,
+    ) -> Result<()> {
+        ctx.accounts.set_dynamic_fee(enabled, min_fee, max_fee)
+    }
+
+    pub fn update_max_trade(ctx: Context<Admin>, max_trade_bps: u16) -> Result<()> {
+        ctx.accounts.update_max_trade(max_trade_bps)
+    }
+
+    pub fn update_flash_loan_fee(ctx: Context<Admin>, flash_loan_fee: u16) -> Result<()> {
+        ctx.accounts.update_flash_loan_fee(flash_loan_fee)
+    }
+
+    pub fn flash_loan(ctx: Context<FlashLoan>, amount: u64, is_x: bool) -> Result<()> {
+        ctx.accounts.flash_loan(amount, is_x)
+    }
+
+    pub fn flash_repay(ctx: Context<FlashLoan>) -> Result<()> {
+        ctx.accounts.flash_repay()
+    }
+
+    pub fn propose_authority(ctx: Context<Admin>, new_authority: Pubkey) -> Result<()> {
+        ctx.accounts.propose_authority(new_authority)
+    }
+
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        ctx.accounts.accept_authority()
+    }
+
+    pub fn freeze_pool(ctx: Context<FreezePool>) -> Result<()> {
+        ctx.accounts.freeze_pool()
+    }
+
+    pub fn close_pool<'info>(ctx: Context<'_, '_, 'info, 'info, ClosePool<'info>>) -> Result<()> {
+        ctx.accounts
+            .close_pool(ctx.remaining_accounts, ctx.bumps.dead)
+    }
+
+    pub fn initialize_farm(ctx: Context<InitFarm>) -> Result<()> {
+        ctx.accounts.init_farm(&ctx.bumps)
+    }
+
+    pub fn fund_rewards(ctx: Context<FundRewards>, amount: u64, duration: i64) -> Result<()> {
+        ctx.accounts.fund_rewards(amount, duration)
+    }
+
+    pub fn stake(ctx: Context<ModifyStake>, amount: u64) -> Result<()> {
+        ctx.accounts.stake(amount, &ctx.bumps)
+    }
+
+    pub fn unstake(ctx: Context<ModifyStake>, amount: u64) -> Result<()> {
+        ctx.accounts.unstake(amount)
+    }
+
+    pub fn claim(ctx: Context<ClaimRewards>) -> Result<()> {
+        ctx.accounts.claim()
     }
 }
@@ -1,18 +1,986 @@
-use anchor_lang::prelude::*;
+use amm_math::{
+    add_liquidity_delta, decay_volatility, dynamic_fee, fee_growth_delta, fees_from_growth,
+    initial_liquidity, price_move_bps, quote_swap, quote_swap_exact_out, Curve, MAX_TICK, MIN_TICK,
+};
+use anchor_lang::{prelude::*, Discriminator};
+
+use crate::AmmError;
+
+pub use amm_math::{SwapQuote, PRICE_PRECISION};
+
+/// LP permanently locked on the first deposit of every pool.
+pub const MINIMUM_LIQUIDITY: u64 = 1_000;
+
+/// Bounds on the StableSwap amplification coefficient.
+pub const MIN_AMP: u64 = 1;
+pub const MAX_AMP: u64 = 1_000_000;
+
+/// Largest factor the amplification may move by in a single ramp.
+pub const MAX_AMP_CHANGE: u64 = 10;
+
+/// Shortest allowed amplification ramp, in seconds.
+pub const MIN_RAMP_DURATION: i64 = 86_400;
+
+/// Smallest tick spacing a concentrated pool may use; bounded by the size of
+/// its tick bitmap.
+pub const MIN_TICK_SPACING: u16 = 8;
+
+/// Words in a concentrated pool's tick bitmap, one bit per usable tick.
+pub const TICK_BITMAP_WORDS: usize = 1024;
+
+/// Number of cumulative price snapshots kept by each pool oracle.
+pub const OBSERVATIONS: usize = 32;
+
+/// Minimum number of seconds between two recorded oracle observations.
+pub const OBSERVATION_INTERVAL: i64 = 300;
+
+/// Pools a single pair index can list.
+pub const MAX_PAIR_POOLS: usize = 16;
+
+/// Seconds over which a pool's recorded volatility decays back to zero.
+pub const VOLATILITY_DECAY_PERIOD: i64 = 600;
+
+/// Shortest reward schedule `fund_rewards` accepts, in seconds.
+pub const MIN_REWARD_DURATION: i64 = 3_600;
+
+/// Fails once `expiration` or, when given, the slot after `max_slot` is
+/// reached. The slot deadline holds even where validators skew the clock.
+pub fn check_deadline(clock: &Clock, expiration: i64, max_slot: Option<u64>) -> Result<()> {
+    require!(expiration > clock.unix_timestamp, AmmError::Expired);
+    if let Some(max_slot) = max_slot {
+        require!(clock.slot <= max_slot, AmmError::SlotDeadlineExceeded);
+    }
+
+    Ok(())
+}
+
+/// Layout version of `Config`. Bump it whenever fields are added, and teach
+/// `migrate_auth` to upgrade accounts in the previous layout.
+pub const CONFIG_VERSION: u8 = 1;
 
 #[account]
 pub struct Config {
+    pub version: u8,
     pub seed: u64,
     pub authority: Pubkey,
+    pub pending_authority: Option<Pubkey>,
     pub mint_x: Pubkey,
     pub mint_y: Pubkey,
+    /// Token accounts holding the pool reserves, fixed at init.
+    pub vault_x: Pubkey,
+    pub vault_y: Pubkey,
     pub fee: u16,
+    pub protocol_fee: u16,
     pub locked: bool,
+    /// Set by `freeze_pool`: the pool stays locked for good and only
+    /// `withdraw` remains open until `close_pool`.
+    pub closing: bool,
+    pub curve: CurveType,
+    /// StableSwap amplification ramp; the coefficient moves linearly from
+    /// `initial_amp` at `ramp_start_ts` to `target_amp` at `ramp_stop_ts`.
+    /// Unused by constant-product pools.
+    pub initial_amp: u64,
+    pub target_amp: u64,
+    pub ramp_start_ts: i64,
+    pub ramp_stop_ts: i64,
+    /// Fee on flash loans in bps, paid into the pool vault for LPs.
+    pub flash_loan_fee: u16,
+    /// Principal of the flash loan taken in the current transaction, if any.
+    pub flash_loan_amount: u64,
+    pub flash_loan_is_x: bool,
+    /// Dynamic fee mode: the LP fee floats between `min_fee` and `max_fee`
+    /// bps with `volatility` instead of being `fee`.
+    pub dynamic_fee: bool,
+    pub min_fee: u16,
+    pub max_fee: u16,
+    /// Recent movement of the reserve price in bps, accumulated by swaps and
+    /// decaying linearly over `VOLATILITY_DECAY_PERIOD` from
+    /// `volatility_updated_ts`.
+    pub volatility: u32,
+    pub volatility_updated_ts: i64,
+    /// Largest share of either reserve, in bps, a single swap may put in or
+    /// take out. Zero leaves trades uncapped.
+    pub max_trade_bps: u16,
     pub config_bump: u8,
     pub lp_mint_bump: u8,
     pub auth_bump: u8,
+    pub fee_vault_x_bump: u8,
+    pub fee_vault_y_bump: u8,
+    pub lp_lock_bump: u8,
+    pub oracle_bump: u8,
 }
 
 impl Space for Config {
-    const INIT_SPACE: usize = 8 + 8 + 32 + 32 + 32 + 2 + 1 + 1 + 1 + 1;
+    const INIT_SPACE: usize = 8
+        + 1
+        + 8
+        + 32
+        + 33
+        + 32
+        + 32
+        + 32
+        + 32
+        + 2
+        + 2
+        + 1
+        + 1
+        + 1
+        + 8
+        + 8
+        + 8
+        + 8
+        + 2
+        + 8
+        + 1
+        + 1
+        + 2
+        + 2
+        + 4
+        + 8
+        + 2
+        + 1
+        + 1
+        + 1
+        + 1
+        + 1
+        + 1
+        + 1;
+}
+
+/// `Config` as written by pools created before versioning, with the
+/// program-wide `[b"auth"]` PDA as both LP mint authority and pool authority.
+/// Such accounts are too short to load as `Config` until `migrate_auth`
+/// reallocates and rewrites them.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct LegacyConfig {
+    pub seed: u64,
+    pub authority: Pubkey,
+    pub mint_x: Pubkey,
+    pub mint_y: Pubkey,
+    pub fee: u16,
+    pub locked: bool,
+    pub config_bump: u8,
+    pub lp_mint_bump: u8,
+    pub auth_bump: u8,
+}
+
+impl LegacyConfig {
+    pub const SPACE: usize = 8 + 8 + 32 + 32 + 32 + 2 + 1 + 1 + 1 + 1;
+
+    /// Decodes the data of a config account still in the legacy layout.
+    pub fn try_from_account_data(data: &[u8]) -> Result<Self> {
+        require!(
+            data.len() == Self::SPACE && data[..8] == Config::DISCRIMINATOR,
+            AmmError::AlreadyMigrated
+        );
+
+        Self::deserialize(&mut &data[8..]).map_err(|_| error!(ErrorCode::AccountDidNotDeserialize))
+    }
+}
+
+/// Invariant a pool trades on. Fixed when the pool is initialized.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum CurveType {
+    /// `x * y = k`.
+    ConstantProduct,
+    /// Curve-style StableSwap invariant for pegged pairs, flattened around
+    /// the 1:1 price by the amplification coefficient.
+    StableSwap,
+    /// Liquidity concentrated in tick ranges held by individual positions.
+    /// Trades through `swap_concentrated` rather than the pooled LP mint.
+    Concentrated,
+}
+
+impl Config {
+    /// Grows a config account written in an older layout to the current
+    /// `Config::INIT_SPACE`, with `payer` covering the extra rent. The caller
+    /// rewrites the data afterwards.
+    pub fn realloc<'info>(
+        config: &AccountInfo<'info>,
+        payer: &AccountInfo<'info>,
+        system_program: &AccountInfo<'info>,
+    ) -> Result<()> {
+        let rent = Rent::get()?.minimum_balance(Config::INIT_SPACE);
+        let lamports = rent.saturating_sub(config.lamports());
+        if lamports > 0 {
+            let cpi_accounts = anchor_lang::system_program::Transfer {
+                from: payer.clone(),
+                to: config.clone(),
+            };
+            let cpi_ctx = CpiContext::new(system_program.clone(), cpi_accounts);
+            anchor_lang::system_program::transfer(cpi_ctx, lamports)?;
+        }
+
+        config.realloc(Config::INIT_SPACE, true)?;
+
+        Ok(())
+    }
+
+    pub fn check_fees(fee: u16, protocol_fee: u16) -> Result<()> {
+        require!(
+            (fee as u32) + (protocol_fee as u32) < 10_000,
+            AmmError::FeeTooHigh
+        );
+
+        Ok(())
+    }
+
+    pub fn check_flash_loan_fee(flash_loan_fee: u16) -> Result<()> {
+        require!(flash_loan_fee < 10_000, AmmError::FeeTooHigh);
+
+        Ok(())
+    }
+
+    /// Fails while the pool is locked by its authority or has a flash loan
+    /// outstanding, during which its vaults do not reflect its reserves.
+    pub fn check_unlocked(&self) -> Result<()> {
+        require!(!self.locked, AmmError::Locked);
+        require!(self.flash_loan_amount == 0, AmmError::FlashLoanActive);
+
+        Ok(())
+    }
+
+    /// Like `check_unlocked`, but lets holders withdraw from a pool frozen
+    /// for closing.
+    pub fn check_withdrawable(&self) -> Result<()> {
+        require!(!self.locked || self.closing, AmmError::Locked);
+        require!(self.flash_loan_amount == 0, AmmError::FlashLoanActive);
+
+        Ok(())
+    }
+
+    pub fn check_dynamic_fee(min_fee: u16, max_fee: u16, protocol_fee: u16) -> Result<()> {
+        require!(min_fee <= max_fee, AmmError::InvalidFeeBounds);
+        Config::check_fees(max_fee, protocol_fee)
+    }
+
+    /// LP fee in bps currently charged by the pool.
+    pub fn lp_fee(&self) -> Result<u16> {
+        match self.dynamic_fee {
+            true => Ok(dynamic_fee(
+                self.min_fee,
+                self.max_fee,
+                self.volatility_at(Clock::get()?.unix_timestamp),
+            )),
+            false => Ok(self.fee),
+        }
+    }
+
+    pub fn total_fee(&self) -> Result<u16> {
+        Ok(self.lp_fee()? + self.protocol_fee)
+    }
+
+    /// Recorded volatility decayed to `now`.
+    pub fn volatility_at(&self, now: i64) -> u32 {
+        decay_volatility(
+            self.volatility,
+            now - self.volatility_updated_ts,
+            VOLATILITY_DECAY_PERIOD,
+        )
+    }
+
+    /// Accumulates the reserve price movement of a swap from `x_before`/
+    /// `y_before` to `x_after`/`y_after` into the volatility that drives the
+    /// dynamic fee. Does nothing outside dynamic fee mode.
+    pub fn record_price_move(
+        &mut self,
+        x_before: u64,
+        y_before: u64,
+        x_after: u64,
+        y_after: u64,
+        now: i64,
+    ) -> Result<()> {
+        if !self.dynamic_fee {
+            return Ok(());
+        }
+
+        let price_move =
+            price_move_bps(x_before, y_before, x_after, y_after).ok_or(AmmError::IncorrectK)?;
+
+        self.volatility = self.volatility_at(now).saturating_add(price_move);
+        self.volatility_updated_ts = now;
+
+        Ok(())
+    }
+
+    /// Curve swaps and liquidity are priced on; fails for concentrated
+    /// pools, which trade through their own tick ranges.
+    pub fn curve(&self) -> Result<Curve> {
+        match self.curve {
+            CurveType::ConstantProduct => Ok(Curve::ConstantProduct),
+            CurveType::StableSwap => Ok(Curve::StableSwap {
+                amp: self.amp(Clock::get()?.unix_timestamp),
+            }),
+            CurveType::Concentrated => err!(AmmError::InvalidCurve),
+        }
+    }
+
+    /// Exact-input quote against reserves `x`/`y`. `is_x` swaps X for Y. The
+    /// protocol share of the fee is split out but still paid by the user.
+    pub fn quote_swap(
+        &self,
+        x: u64,
+        y: u64,
+        amount_in: u64,
+        min_out: u64,
+        is_x: bool,
+    ) -> Result<SwapQuote> {
+        let (reserve_in, reserve_out) = match is_x {
+            true => (x, y),
+            false => (y, x),
+        };
+
+        let quote = quote_swap(
+            self.curve()?,
+            reserve_in,
+            reserve_out,
+            amount_in,
+            self.lp_fee()?,
+            self.protocol_fee,
+        )
+        .ok_or(AmmError::IncorrectK)?;
+
+        require!(quote.amount_out > 0, AmmError::InvalidWithdraw);
+        require!(quote.amount_out >= min_out, AmmError::SlippageExceeded);
+        self.check_trade_size(&quote, reserve_in, reserve_out)?;
+
+        Ok(quote)
+    }
+
+    /// Exact-output quote against reserves `x`/`y`. `is_x` swaps X for Y.
+    pub fn quote_swap_exact_out(
+        &self,
+        x: u64,
+        y: u64,
+        amount_out: u64,
+        max_in: u64,
+        is_x: bool,
+    ) -> Result<SwapQuote> {
+        let (reserve_in, reserve_out) = match is_x {
+            true => (x, y),
+            false => (y, x),
+        };
+
+        let quote = quote_swap_exact_out(
+            self.curve()?,
+            reserve_in,
+            reserve_out,
+            amount_out,
+            self.lp_fee()?,
+            self.protocol_fee,
+        )
+        .ok_or(AmmError::InvalidAmount)?;
+
+        require!(quote.amount_in <= max_in, AmmError::SlippageExceeded);
+        self.check_trade_size(&quote, reserve_in, reserve_out)?;
+
+        Ok(quote)
+    }
+
+    /// Liquidity minted for the first deposit of `x` and `y`, before
+    /// `MINIMUM_LIQUIDITY` is locked away.
+    pub fn initial_liquidity(&self, x: u64, y: u64) -> Result<u64> {
+        let liquidity = initial_liquidity(self.curve()?, x, y).ok_or(AmmError::InvalidDeposit)?;
+
+        Ok(liquidity)
+    }
+
+    /// Amplification coefficient in effect at `now`.
+    pub fn amp(&self, now: i64) -> u64 {
+        if now >= self.ramp_stop_ts || self.ramp_stop_ts <= self.ramp_start_ts {
+            return self.target_amp;
+        }
+
+        let elapsed = now.saturating_sub(self.ramp_start_ts).max(0) as u128;
+        let duration = (self.ramp_stop_ts - self.ramp_start_ts) as u128;
+        let (initial, target) = (self.initial_amp as u128, self.target_amp as u128);
+
+        let amp = match target > initial {
+            true => initial + (target - initial) * elapsed / duration,
+            false => initial - (initial - target) * elapsed / duration,
+        };
+
+        amp as u64
+    }
+
+    pub fn check_amp(amp: u64) -> Result<()> {
+        require!((MIN_AMP..=MAX_AMP).contains(&amp), AmmError::InvalidAmp);
+
+        Ok(())
+    }
+
+    pub fn check_max_trade(max_trade_bps: u16) -> Result<()> {
+        require!(max_trade_bps <= 10_000, AmmError::InvalidAmount);
+
+        Ok(())
+    }
+
+    /// Fails if `quote` puts in or takes out more than `max_trade_bps` of the
+    /// reserve on that side.
+    fn check_trade_size(&self, quote: &SwapQuote, reserve_in: u64, reserve_out: u64) -> Result<()> {
+        if self.max_trade_bps == 0 {
+            return Ok(());
+        }
+
+        let cap = |reserve: u64| reserve as u128 * self.max_trade_bps as u128 / 10_000;
+        require!(
+            quote.amount_in as u128 <= cap(reserve_in)
+                && quote.amount_out as u128 <= cap(reserve_out),
+            AmmError::TradeTooLarge
+        );
+
+        Ok(())
+    }
+
+    /// Portion of a swap fee owed to the protocol; the remainder stays in the
+    /// vault for LPs.
+    pub fn protocol_fee_share(&self, fee_amount: u64) -> Result<u64> {
+        let share = amm_math::protocol_fee_share(fee_amount, self.lp_fee()?, self.protocol_fee)
+            .ok_or(AmmError::InvalidAmount)?;
+
+        Ok(share)
+    }
+}
+
+/// Time-weighted price accumulators for a pool. Prices are Q64.64 fixed
+/// point: `price_x` is the price of X in Y and `price_y` the price of Y in X.
+/// Cumulatives wrap on overflow; only differences between them are meaningful.
+#[account]
+pub struct Oracle {
+    pub price_x_cumulative: u128,
+    pub price_y_cumulative: u128,
+    pub last_updated: i64,
+    pub observation_index: u8,
+    pub observations: [Observation; OBSERVATIONS],
+}
+
+impl Space for Oracle {
+    const INIT_SPACE: usize = 8 + 16 + 16 + 8 + 1 + OBSERVATIONS * Observation::INIT_SPACE;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct Observation {
+    pub timestamp: i64,
+    pub price_x_cumulative: u128,
+    pub price_y_cumulative: u128,
+}
+
+impl Space for Observation {
+    const INIT_SPACE: usize = 8 + 16 + 16;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct Twap {
+    pub price_x: u128,
+    pub price_y: u128,
+    pub window: i64,
+}
+
+impl Oracle {
+    /// Accrues the prices implied by the reserves `x` and `y`, which must be
+    /// the reserves held since `last_updated`, and records an observation if
+    /// the last one is old enough.
+    pub fn update(&mut self, x: u64, y: u64, now: i64) {
+        let (price_x_cumulative, price_y_cumulative) = self.cumulatives_at(x, y, now);
+        self.price_x_cumulative = price_x_cumulative;
+        self.price_y_cumulative = price_y_cumulative;
+        self.last_updated = self.last_updated.max(now);
+
+        let last = self.observations[self.observation_index as usize];
+        if now - last.timestamp >= OBSERVATION_INTERVAL {
+            self.observation_index = ((self.observation_index as usize + 1) % OBSERVATIONS) as u8;
+            self.observations[self.observation_index as usize] = Observation {
+                timestamp: now,
+                price_x_cumulative,
+                price_y_cumulative,
+            };
+        }
+    }
+
+    /// TWAP from the newest observation at least `seconds_ago` old up to now.
+    pub fn observe(&self, x: u64, y: u64, now: i64, seconds_ago: i64) -> Option<Twap> {
+        let target = now.checked_sub(seconds_ago)?;
+        let start = self
+            .observations
+            .iter()
+            .filter(|o| o.timestamp > 0 && o.timestamp <= target)
+            .max_by_key(|o| o.timestamp)?;
+
+        let window = now - start.timestamp;
+        if window <= 0 {
+            return None;
+        }
+
+        let (price_x_cumulative, price_y_cumulative) = self.cumulatives_at(x, y, now);
+
+        Some(Twap {
+            price_x: price_x_cumulative.wrapping_sub(start.price_x_cumulative) / window as u128,
+            price_y: price_y_cumulative.wrapping_sub(start.price_y_cumulative) / window as u128,
+            window,
+        })
+    }
+
+    fn cumulatives_at(&self, x: u64, y: u64, now: i64) -> (u128, u128) {
+        let elapsed = now.saturating_sub(self.last_updated);
+        if elapsed <= 0 || x == 0 || y == 0 {
+            return (self.price_x_cumulative, self.price_y_cumulative);
+        }
+
+        let price_x = ((y as u128) << 64) / x as u128;
+        let price_y = ((x as u128) << 64) / y as u128;
+
+        (
+            self.price_x_cumulative
+                .wrapping_add(price_x.wrapping_mul(elapsed as u128)),
+            self.price_y_cumulative
+                .wrapping_add(price_y.wrapping_mul(elapsed as u128)),
+        )
+    }
+}
+
+/// Price and liquidity state of a `CurveType::Concentrated` pool. Tokens are
+/// still held in the `Config` vaults.
+#[account]
+pub struct ConcentratedPool {
+    /// Current sqrt price of X in Y, Q64.64.
+    pub sqrt_price: u128,
+    pub tick_current: i32,
+    pub tick_spacing: u16,
+    /// Liquidity of the positions in range at the current price.
+    pub liquidity: u128,
+    /// LP fees earned per unit of liquidity over the life of the pool, Q64.64.
+    /// Wrap on overflow; only differences between them are meaningful.
+    pub fee_growth_global_x: u128,
+    pub fee_growth_global_y: u128,
+    pub bump: u8,
+    pub bitmap_bump: u8,
+}
+
+impl Space for ConcentratedPool {
+    const INIT_SPACE: usize = 8 + 16 + 4 + 2 + 16 + 16 + 16 + 1 + 1;
+}
+
+impl ConcentratedPool {
+    pub fn check_tick(&self, tick: i32) -> Result<()> {
+        require!(
+            (MIN_TICK..=MAX_TICK).contains(&tick) && tick % self.tick_spacing as i32 == 0,
+            AmmError::InvalidTick
+        );
+
+        Ok(())
+    }
+
+    /// Fee growth inside `[lower, upper)` per unit of liquidity.
+    pub fn fee_growth_inside(&self, lower: &Tick, upper: &Tick) -> (u128, u128) {
+        let (below_x, below_y) = match self.tick_current >= lower.index {
+            true => (lower.fee_growth_outside_x, lower.fee_growth_outside_y),
+            false => (
+                self.fee_growth_global_x
+                    .wrapping_sub(lower.fee_growth_outside_x),
+                self.fee_growth_global_y
+                    .wrapping_sub(lower.fee_growth_outside_y),
+            ),
+        };
+
+        let (above_x, above_y) = match self.tick_current < upper.index {
+            true => (upper.fee_growth_outside_x, upper.fee_growth_outside_y),
+            false => (
+                self.fee_growth_global_x
+                    .wrapping_sub(upper.fee_growth_outside_x),
+                self.fee_growth_global_y
+                    .wrapping_sub(upper.fee_growth_outside_y),
+            ),
+        };
+
+        (
+            self.fee_growth_global_x
+                .wrapping_sub(below_x)
+                .wrapping_sub(above_x),
+            self.fee_growth_global_y
+                .wrapping_sub(below_y)
+                .wrapping_sub(above_y),
+        )
+    }
+}
+
+/// One bit per usable tick of a concentrated pool, set while the tick is
+/// referenced by a position. Lets swaps find the next tick to cross without
+/// trusting the caller.
+#[account(zero_copy)]
+pub struct TickBitmap {
+    pub words: [u64; TICK_BITMAP_WORDS],
+}
+
+impl Space for TickBitmap {
+    const INIT_SPACE: usize = 8 + 8 * TICK_BITMAP_WORDS;
+}
+
+impl TickBitmap {
+    fn position(tick: i32, tick_spacing: u16) -> i32 {
+        let spacing = tick_spacing as i32;
+        tick.div_euclid(spacing) - MIN_TICK.div_euclid(spacing)
+    }
+
+    fn tick_at(position: usize, tick_spacing: u16) -> i32 {
+        let spacing = tick_spacing as i32;
+        (position as i32 + MIN_TICK.div_euclid(spacing)) * spacing
+    }
+
+    pub fn set(&mut self, tick: i32, tick_spacing: u16, initialized: bool) {
+        let position = Self::position(tick, tick_spacing) as usize;
+        let mask = 1u64 << (position % 64);
+
+        match initialized {
+            true => self.words[position / 64] |= mask,
+            false => self.words[position / 64] &= !mask,
+        }
+    }
+
+    /// Nearest initialized tick at or below `tick` when `lte`, otherwise the
+    /// nearest one strictly above it.
+    pub fn next_initialized(&self, tick: i32, tick_spacing: u16, lte: bool) -> Option<i32> {
+        let bits = TICK_BITMAP_WORDS * 64;
+        let position = Self::position(tick, tick_spacing);
+
+        match lte {
+            true => {
+                if position < 0 {
+                    return None;
+                }
+
+                let position = (position as usize).min(bits - 1);
+                let mut word = position / 64;
+                let mut mask = u64::MAX >> (63 - position % 64);
+                loop {
+                    let set = self.words[word] & mask;
+                    if set != 0 {
+                        let bit = 63 - set.leading_zeros() as usize;
+                        return Some(Self::tick_at(word * 64 + bit, tick_spacing));
+                    }
+                    if word == 0 {
+                        return None;
+                    }
+                    word -= 1;
+                    mask = u64::MAX;
+                }
+            }
+            false => {
+                let position = (position + 1).max(0) as usize;
+                if position >= bits {
+                    return None;
+                }
+
+                let mut word = position / 64;
+                let mut mask = u64::MAX << (position % 64);
+                loop {
+                    let set = self.words[word] & mask;
+                    if set != 0 {
+                        let bit = set.trailing_zeros() as usize;
+                        return Some(Self::tick_at(word * 64 + bit, tick_spacing));
+                    }
+                    word += 1;
+                    if word == TICK_BITMAP_WORDS {
+                        return None;
+                    }
+                    mask = u64::MAX;
+                }
+            }
+        }
+    }
+}
+
+/// Liquidity boundary of a concentrated pool, shared by every position with
+/// an end at `index`.
+#[account]
+pub struct Tick {
+    pub config: Pubkey,
+    pub index: i32,
+    pub liquidity_gross: u128,
+    /// Liquidity added to the pool when the price crosses this tick upwards.
+    pub liquidity_net: i128,
+    /// Fee growth on the other side of this tick from the current price.
+    pub fee_growth_outside_x: u128,
+    pub fee_growth_outside_y: u128,
+    pub bump: u8,
+}
+
+impl Space for Tick {
+    const INIT_SPACE: usize = 8 + 32 + 4 + 16 + 16 + 16 + 16 + 1;
+}
+
+impl Tick {
+    /// Adds `liquidity_delta` of a position starting (`upper == false`) or
+    /// ending here. Returns whether the tick went from unused to used or
+    /// back, in which case its bitmap bit has to be flipped.
+    pub fn update(
+        &mut self,
+        liquidity_delta: i128,
+        upper: bool,
+        pool: &ConcentratedPool,
+    ) -> Result<bool> {
+        let gross_before = self.liquidity_gross;
+        let gross_after =
+            add_liquidity_delta(gross_before, liquidity_delta).ok_or(AmmError::InvalidAmount)?;
+
+        // By convention all growth before a tick is initialized happened
+        // below it.
+        if gross_before == 0 {
+            (self.fee_growth_outside_x, self.fee_growth_outside_y) =
+                match self.index <= pool.tick_current {
+                    true => (pool.fee_growth_global_x, pool.fee_growth_global_y),
+                    false => (0, 0),
+                };
+        }
+
+        self.liquidity_gross = gross_after;
+        self.liquidity_net = match upper {
+            true => self.liquidity_net.checked_sub(liquidity_delta),
+            false => self.liquidity_net.checked_add(liquidity_delta),
+        }
+        .ok_or(AmmError::InvalidAmount)?;
+
+        Ok((gross_before == 0) != (gross_after == 0))
+    }
+
+    /// Flips the fee growth to the new side of the price. Returns the
+    /// liquidity to add when crossing upwards.
+    pub fn cross(&mut self, fee_growth_global_x: u128, fee_growth_global_y: u128) -> i128 {
+        self.fee_growth_outside_x = fee_growth_global_x.wrapping_sub(self.fee_growth_outside_x);
+        self.fee_growth_outside_y = fee_growth_global_y.wrapping_sub(self.fee_growth_outside_y);
+
+        self.liquidity_net
+    }
+}
+
+/// Liquidity provided by `owner` to a concentrated pool between two ticks.
+#[account]
+pub struct Position {
+    pub config: Pubkey,
+    pub owner: Pubkey,
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    pub liquidity: u128,
+    pub fee_growth_inside_x_last: u128,
+    pub fee_growth_inside_y_last: u128,
+    pub fees_owed_x: u64,
+    pub fees_owed_y: u64,
+    pub bump: u8,
+}
+
+impl Space for Position {
+    const INIT_SPACE: usize = 8 + 32 + 32 + 4 + 4 + 16 + 16 + 16 + 8 + 8 + 1;
+}
+
+impl Position {
+    /// Credits the fees earned since the last update, given the current fee
+    /// growth inside the position's range.
+    pub fn accrue_fees(
+        &mut self,
+        fee_growth_inside_x: u128,
+        fee_growth_inside_y: u128,
+    ) -> Result<()> {
+        let earned_x = fees_from_growth(
+            fee_growth_inside_x.wrapping_sub(self.fee_growth_inside_x_last),
+            self.liquidity,
+        );
+        let earned_y = fees_from_growth(
+            fee_growth_inside_y.wrapping_sub(self.fee_growth_inside_y_last),
+            self.liquidity,
+        );
+
+        self.fees_owed_x = earned_x
+            .and_then(|fees| self.fees_owed_x.checked_add(fees))
+            .ok_or(AmmError::InvalidAmount)?;
+        self.fees_owed_y = earned_y
+            .and_then(|fees| self.fees_owed_y.checked_add(fees))
+            .ok_or(AmmError::InvalidAmount)?;
+        self.fee_growth_inside_x_last = fee_growth_inside_x;
+        self.fee_growth_inside_y_last = fee_growth_inside_y;
+
+        Ok(())
+    }
+}
+
+/// Program-wide pool counters, created by the first `initialize_amm`.
+#[account]
+pub struct Registry {
+    pub pools: u64,
+    pub pairs: u64,
+    pub bump: u8,
+}
+
+impl Space for Registry {
+    const INIT_SPACE: usize = 8 + 8 + 8 + 1;
+}
+
+/// Every pool of a mint pair, seeded by the pair in canonical order so
+/// routers can find them from the mints alone.
+#[account]
+pub struct PairIndex {
+    pub mint_x: Pubkey,
+    pub mint_y: Pubkey,
+    pub pools: Vec<PoolEntry>,
+    pub bump: u8,
+}
+
+impl Space for PairIndex {
+    const INIT_SPACE: usize = 8 + 32 + 32 + 4 + MAX_PAIR_POOLS * PoolEntry::INIT_SPACE + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct PoolEntry {
+    pub config: Pubkey,
+    pub curve: CurveType,
+    /// LP fee in bps at creation.
+    pub fee: u16,
+    pub canonical: bool,
+}
+
+impl Space for PoolEntry {
+    const INIT_SPACE: usize = 32 + 1 + 2 + 1;
+}
+
+impl PairIndex {
+    /// Lists `config`. The first pool of each curve and fee tier becomes its
+    /// canonical pool; returns whether this one did.
+    pub fn add(&mut self, config: Pubkey, curve: CurveType, fee: u16) -> Result<bool> {
+        require!(self.pools.len() < MAX_PAIR_POOLS, AmmError::PairIndexFull);
+
+        let canonical = self.canonical(curve, fee).is_none();
+        self.pools.push(PoolEntry {
+            config,
+            curve,
+            fee,
+            canonical,
+        });
+
+        Ok(canonical)
+    }
+
+    /// Delists `config`. If it was canonical, the oldest remaining pool of
+    /// the same curve and fee tier takes its place.
+    pub fn remove(&mut self, config: Pubkey) {
+        let Some(index) = self.pools.iter().position(|p| p.config == config) else {
+            return;
+        };
+
+        let removed = self.pools.remove(index);
+        if removed.canonical {
+            if let Some(next) = self
+                .pools
+                .iter_mut()
+                .find(|p| p.curve == removed.curve && p.fee == removed.fee)
+            {
+                next.canonical = true;
+            }
+        }
+    }
+
+    /// Canonical pool of the pair for `curve` and `fee`.
+    pub fn canonical(&self, curve: CurveType, fee: u16) -> Option<Pubkey> {
+        self.pools
+            .iter()
+            .find(|p| p.canonical && p.curve == curve && p.fee == fee)
+            .map(|p| p.config)
+    }
+}
+
+/// Liquidity mining for a pool: LP staked in the farm earns `reward_mint`
+/// streamed at `reward_rate` per second until `reward_end_ts`.
+#[account]
+pub struct Farm {
+    pub config: Pubkey,
+    pub reward_mint: Pubkey,
+    /// Rewards streamed per second, shared by all stakers.
+    pub reward_rate: u64,
+    pub reward_end_ts: i64,
+    pub last_update_ts: i64,
+    /// Rewards earned per staked LP since the farm opened, as a Q64.64.
+    pub reward_per_share: u128,
+    pub total_staked: u64,
+    pub bump: u8,
+    pub stake_vault_bump: u8,
+    pub reward_vault_bump: u8,
+}
+
+impl Space for Farm {
+    const INIT_SPACE: usize = 8 + 32 + 32 + 8 + 8 + 8 + 16 + 8 + 1 + 1 + 1;
+}
+
+impl Farm {
+    /// Streams the rewards emitted since the last update into
+    /// `reward_per_share`. Rewards emitted while nothing is staked are not
+    /// distributed.
+    pub fn update(&mut self, now: i64) -> Result<()> {
+        let until = now.min(self.reward_end_ts);
+
+        if until > self.last_update_ts && self.total_staked > 0 {
+            let reward =
+                u64::try_from(self.reward_rate as u128 * (until - self.last_update_ts) as u128)
+                    .map_err(|_| AmmError::InvalidAmount)?;
+
+            self.reward_per_share = fee_growth_delta(reward, self.total_staked as u128)
+                .and_then(|delta| self.reward_per_share.checked_add(delta))
+                .ok_or(AmmError::InvalidAmount)?;
+        }
+
+        self.last_update_ts = self.last_update_ts.max(now);
+
+        Ok(())
+    }
+
+    /// Adds `amount` of rewards to the schedule, streaming it together with
+    /// whatever is still unstreamed over the next `duration` seconds.
+    pub fn fund(&mut self, amount: u64, duration: i64, now: i64) -> Result<()> {
+        require!(
+            duration >= MIN_REWARD_DURATION,
+            AmmError::InvalidRewardSchedule
+        );
+
+        let remaining = match self.reward_end_ts > now {
+            true => self.reward_rate as u128 * (self.reward_end_ts - now) as u128,
+            false => 0,
+        };
+        let reward_rate = u64::try_from((amount as u128 + remaining) / duration as u128)
+            .map_err(|_| AmmError::InvalidAmount)?;
+        require!(reward_rate > 0, AmmError::InvalidRewardSchedule);
+
+        self.reward_rate = reward_rate;
+        self.reward_end_ts = now
+            .checked_add(duration)
+            .ok_or(AmmError::InvalidRewardSchedule)?;
+
+        Ok(())
+    }
+}
+
+/// LP staked by `owner` in a farm.
+#[account]
+pub struct Stake {
+    pub farm: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub reward_per_share_last: u128,
+    pub rewards_owed: u64,
+    pub bump: u8,
+}
+
+impl Space for Stake {
+    const INIT_SPACE: usize = 8 + 32 + 32 + 8 + 16 + 8 + 1;
+}
+
+impl Stake {
+    /// Credits the rewards earned since the last update, given the farm's
+    /// current reward per share.
+    pub fn accrue_rewards(&mut self, reward_per_share: u128) -> Result<()> {
+        self.rewards_owed = fees_from_growth(
+            reward_per_share - self.reward_per_share_last,
+            self.amount as u128,
+        )
+        .and_then(|rewards| self.rewards_owed.checked_add(rewards))
+        .ok_or(AmmError::InvalidAmount)?;
+        self.reward_per_share_last = reward_per_share;
+
+        Ok(())
+    }
 }
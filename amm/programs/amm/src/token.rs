@@ -0,0 +1,44 @@
+//! Program-side wrappers over `token-extensions`, failing with `AmmError`.
+
+use anchor_lang::prelude::*;
+
+use crate::AmmError;
+
+/// Rejects Token-2022 mints with extensions the pool does not support.
+pub fn check_mint<'info>(mint: &impl ToAccountInfo<'info>) -> Result<()> {
+    require!(
+        token_extensions::is_supported_mint(&mint.to_account_info())?,
+        AmmError::UnsupportedMint
+    );
+
+    Ok(())
+}
+
+/// Whether the mint charges transfer fees.
+pub fn has_transfer_fee<'info>(mint: &impl ToAccountInfo<'info>) -> Result<bool> {
+    Ok(token_extensions::has_transfer_fee(&mint.to_account_info())?)
+}
+
+/// Fee withheld by the mint when `amount` is transferred.
+pub fn transfer_fee<'info>(mint: &impl ToAccountInfo<'info>, amount: u64) -> Result<u64> {
+    let fee = token_extensions::transfer_fee(&mint.to_account_info(), amount)?
+        .ok_or(AmmError::InvalidAmount)?;
+
+    Ok(fee)
+}
+
+/// Amount received when `amount` is transferred.
+pub fn net_amount<'info>(mint: &impl ToAccountInfo<'info>, amount: u64) -> Result<u64> {
+    let net = token_extensions::net_amount(&mint.to_account_info(), amount)?
+        .ok_or(AmmError::InvalidAmount)?;
+
+    Ok(net)
+}
+
+/// Amount to transfer so that exactly `net` is received.
+pub fn gross_amount<'info>(mint: &impl ToAccountInfo<'info>, net: u64) -> Result<u64> {
+    let gross = token_extensions::gross_amount(&mint.to_account_info(), net)?
+        .ok_or(AmmError::InvalidAmount)?;
+
+    Ok(gross)
+}
@@ -1,16 +1,1075 @@
 import * as anchor from "@coral-xyz/anchor";
-import { Program } from "@coral-xyz/anchor";
+
+import {
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  MINT_SIZE,
+  TOKEN_PROGRAM_ID,
+  createAssociatedTokenAccountIdempotentInstruction,
+  createInitializeMint2Instruction,
+  createMintToInstruction,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getMinimumBalanceForRentExemptMint,
+} from "@solana/spl-token";
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  Transaction,
+} from "@solana/web3.js";
+
 import { Amm } from "../target/types/amm";
+import { Program } from "@coral-xyz/anchor";
+import { assert } from "chai";
+import { randomBytes } from "crypto";
 
 describe("amm", () => {
   // Configure the client to use the local cluster.
   anchor.setProvider(anchor.AnchorProvider.env());
-
+  const provider = anchor.getProvider();
   const program = anchor.workspace.Amm as Program<Amm>;
+  const connection = program.provider.connection;
+
+  const admin = Keypair.generate();
+  const newAuthority = Keypair.generate();
+  const user = Keypair.generate();
+  const fee = 25;
+  const protocolFee = 5;
+  const flashLoanFee = 9;
+
+  const seed = new anchor.BN(randomBytes(8));
+  // Pools require mint X to sort before mint Y.
+  const [mintX, mintY] = [Keypair.generate(), Keypair.generate()].sort((a, b) =>
+    a.publicKey.toBuffer().compare(b.publicKey.toBuffer())
+  );
+  const vaultX = Keypair.generate();
+  const vaultY = Keypair.generate();
+
+  const config = PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("config"),
+      seed.toArrayLike(Buffer, "le", 8),
+      mintX.publicKey.toBuffer(),
+      mintY.publicKey.toBuffer(),
+    ],
+    program.programId
+  )[0];
+  const mintLp = PublicKey.findProgramAddressSync(
+    [Buffer.from("lp"), config.toBuffer()],
+    program.programId
+  )[0];
+  const auth = PublicKey.findProgramAddressSync(
+    [Buffer.from("auth"), config.toBuffer()],
+    program.programId
+  )[0];
+  const feeVaultX = PublicKey.findProgramAddressSync(
+    [Buffer.from("fee_x"), config.toBuffer()],
+    program.programId
+  )[0];
+  const feeVaultY = PublicKey.findProgramAddressSync(
+    [Buffer.from("fee_y"), config.toBuffer()],
+    program.programId
+  )[0];
+  const dead = PublicKey.findProgramAddressSync(
+    [Buffer.from("dead"), config.toBuffer()],
+    program.programId
+  )[0];
+  const lpLock = PublicKey.findProgramAddressSync(
+    [Buffer.from("lp_lock"), config.toBuffer()],
+    program.programId
+  )[0];
+  const oracle = PublicKey.findProgramAddressSync(
+    [Buffer.from("oracle"), config.toBuffer()],
+    program.programId
+  )[0];
+  const registry = PublicKey.findProgramAddressSync(
+    [Buffer.from("registry")],
+    program.programId
+  )[0];
+  const pair = PublicKey.findProgramAddressSync(
+    [Buffer.from("pair"), mintX.publicKey.toBuffer(), mintY.publicKey.toBuffer()],
+    program.programId
+  )[0];
+  const minimumLiquidity = 1000;
+  const farm = PublicKey.findProgramAddressSync(
+    [Buffer.from("farm"), config.toBuffer()],
+    program.programId
+  )[0];
+  const stakeVault = PublicKey.findProgramAddressSync(
+    [Buffer.from("farm_lp"), farm.toBuffer()],
+    program.programId
+  )[0];
+  const rewardVault = PublicKey.findProgramAddressSync(
+    [Buffer.from("farm_rewards"), farm.toBuffer()],
+    program.programId
+  )[0];
+  const stake = PublicKey.findProgramAddressSync(
+    [Buffer.from("stake"), farm.toBuffer(), user.publicKey.toBuffer()],
+    program.programId
+  )[0];
+
+  const userAtaX = getAssociatedTokenAddressSync(
+    mintX.publicKey,
+    user.publicKey
+  );
+  const userAtaY = getAssociatedTokenAddressSync(
+    mintY.publicKey,
+    user.publicKey
+  );
+  const userAtaLp = getAssociatedTokenAddressSync(mintLp, user.publicKey);
+  const treasuryX = getAssociatedTokenAddressSync(
+    mintX.publicKey,
+    admin.publicKey
+  );
+  const treasuryY = getAssociatedTokenAddressSync(
+    mintY.publicKey,
+    admin.publicKey
+  );
+
+  const clSeed = new anchor.BN(randomBytes(8));
+  const clVaultX = Keypair.generate();
+  const clVaultY = Keypair.generate();
+  const tickLower = -800;
+  const tickUpper = 800;
+
+  const pda = (...seeds: Buffer[]) =>
+    PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+  const tickSeed = (tick: number) => {
+    const buffer = Buffer.alloc(4);
+    buffer.writeInt32LE(tick);
+    return buffer;
+  };
+
+  const clConfig = pda(
+    Buffer.from("config"),
+    clSeed.toArrayLike(Buffer, "le", 8),
+    mintX.publicKey.toBuffer(),
+    mintY.publicKey.toBuffer()
+  );
+  const clPool = pda(Buffer.from("concentrated"), clConfig.toBuffer());
+  const bitmap = pda(Buffer.from("bitmap"), clConfig.toBuffer());
+  const lowerTick = pda(
+    Buffer.from("tick"),
+    clConfig.toBuffer(),
+    tickSeed(tickLower)
+  );
+  const upperTick = pda(
+    Buffer.from("tick"),
+    clConfig.toBuffer(),
+    tickSeed(tickUpper)
+  );
+  const position = pda(
+    Buffer.from("position"),
+    clConfig.toBuffer(),
+    user.publicKey.toBuffer(),
+    tickSeed(tickLower),
+    tickSeed(tickUpper)
+  );
+
+  const positionAccounts = () => ({
+    owner: user.publicKey,
+    config: clConfig,
+    mintX: mintX.publicKey,
+    mintY: mintY.publicKey,
+    pool: clPool,
+    bitmap,
+    position,
+    lowerTick,
+    upperTick,
+    userXAta: userAtaX,
+    userYAta: userAtaY,
+    vaultX: clVaultX.publicKey,
+    vaultY: clVaultY.publicKey,
+    tokenProgram: TOKEN_PROGRAM_ID,
+  });
+
+  const expiration = () => new anchor.BN(Math.floor(Date.now() / 1000) + 600);
+
+  const balance = async (address: PublicKey): Promise<bigint> =>
+    (await getAccount(connection, address)).amount;
+
+  const poolAccounts = () => ({
+    user: user.publicKey,
+    config,
+    mintLp,
+    mintX: mintX.publicKey,
+    mintY: mintY.publicKey,
+    userXAta: userAtaX,
+    userYAta: userAtaY,
+    vaultX: vaultX.publicKey,
+    vaultY: vaultY.publicKey,
+    feeVaultX,
+    feeVaultY,
+    oracle,
+    associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+    tokenProgram: TOKEN_PROGRAM_ID,
+    systemProgram: anchor.web3.SystemProgram.programId,
+  });
+
+  const confirm = async (signature: string): Promise<string> => {
+    const block = await connection.getLatestBlockhash();
+    await connection.confirmTransaction({
+      signature,
+      ...block,
+    });
+    return signature;
+  };
+
+  const log = async (signature: string): Promise<string> => {
+    console.log(
+      `Your transaction signature: https://explorer.solana.com/transaction/${signature}?cluster=custom&customUrl=${connection.rpcEndpoint}`
+    );
+    return signature;
+  };
+
+  it("Airdrop", async () => {
+    await Promise.all([
+      await connection
+        .requestAirdrop(admin.publicKey, LAMPORTS_PER_SOL * 10)
+        .then(confirm),
+      await connection
+        .requestAirdrop(user.publicKey, LAMPORTS_PER_SOL * 10)
+        .then(confirm),
+    ]);
+  });
+
+  it("Setup mints", async () => {
+    const lamports = await getMinimumBalanceForRentExemptMint(connection);
+    let tx = new Transaction();
+    tx.instructions = [
+      anchor.web3.SystemProgram.createAccount({
+        fromPubkey: provider.publicKey,
+        newAccountPubkey: mintX.publicKey,
+        lamports,
+        space: MINT_SIZE,
+        programId: TOKEN_PROGRAM_ID,
+      }),
+      anchor.web3.SystemProgram.createAccount({
+        fromPubkey: provider.publicKey,
+        newAccountPubkey: mintY.publicKey,
+        lamports,
+        space: MINT_SIZE,
+        programId: TOKEN_PROGRAM_ID,
+      }),
+      createInitializeMint2Instruction(mintX.publicKey, 6, user.publicKey, null),
+      createInitializeMint2Instruction(mintY.publicKey, 6, user.publicKey, null),
+      createAssociatedTokenAccountIdempotentInstruction(
+        provider.publicKey,
+        userAtaX,
+        user.publicKey,
+        mintX.publicKey
+      ),
+      createAssociatedTokenAccountIdempotentInstruction(
+        provider.publicKey,
+        userAtaY,
+        user.publicKey,
+        mintY.publicKey
+      ),
+      createAssociatedTokenAccountIdempotentInstruction(
+        provider.publicKey,
+        treasuryX,
+        admin.publicKey,
+        mintX.publicKey
+      ),
+      createAssociatedTokenAccountIdempotentInstruction(
+        provider.publicKey,
+        treasuryY,
+        admin.publicKey,
+        mintY.publicKey
+      ),
+      createMintToInstruction(mintX.publicKey, userAtaX, user.publicKey, 1000e6),
+      createMintToInstruction(mintY.publicKey, userAtaY, user.publicKey, 1000e6),
+    ];
+
+    await provider.sendAndConfirm(tx, [mintX, mintY, user]).then(log);
+  });
+
+  it("Initialize", async () => {
+    await program.methods
+      .initializeAmm(
+        seed,
+        fee,
+        protocolFee,
+        { constantProduct: {} },
+        new anchor.BN(0),
+        flashLoanFee
+      )
+      .accounts({
+        initializer: admin.publicKey,
+        config,
+        mintLp,
+        auth,
+        dead,
+        lpLock,
+        mintX: mintX.publicKey,
+        mintY: mintY.publicKey,
+        vaultX: vaultX.publicKey,
+        vaultY: vaultY.publicKey,
+        feeVaultX,
+        feeVaultY,
+        oracle,
+        registry,
+        pair,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([admin, vaultX, vaultY])
+      .rpc()
+      .then(confirm)
+      .then(log);
+
+    const state = await program.account.config.fetch(config);
+    assert.ok(state.authority.equals(admin.publicKey));
+    assert.equal(state.fee, fee);
+    assert.equal(state.protocolFee, protocolFee);
+    assert.isFalse(state.locked);
+
+    const index = await program.account.pairIndex.fetch(pair);
+    assert.ok(index.mintX.equals(mintX.publicKey));
+    assert.equal(index.pools.length, 1);
+    assert.ok(index.pools[0].config.equals(config));
+    assert.isTrue(index.pools[0].canonical);
+  });
+
+  it("Rejects unordered mints", async () => {
+    const unordered = pda(
+      Buffer.from("config"),
+      seed.toArrayLike(Buffer, "le", 8),
+      mintY.publicKey.toBuffer(),
+      mintX.publicKey.toBuffer()
+    );
+    const vaults = [Keypair.generate(), Keypair.generate()];
+
+    try {
+      await program.methods
+        .initializeAmm(
+          seed,
+          fee,
+          protocolFee,
+          { constantProduct: {} },
+          new anchor.BN(0),
+          flashLoanFee
+        )
+        .accounts({
+          initializer: admin.publicKey,
+          config: unordered,
+          mintLp: pda(Buffer.from("lp"), unordered.toBuffer()),
+          auth: pda(Buffer.from("auth"), unordered.toBuffer()),
+          dead: pda(Buffer.from("dead"), unordered.toBuffer()),
+          lpLock: pda(Buffer.from("lp_lock"), unordered.toBuffer()),
+          mintX: mintY.publicKey,
+          mintY: mintX.publicKey,
+          vaultX: vaults[0].publicKey,
+          vaultY: vaults[1].publicKey,
+          feeVaultX: pda(Buffer.from("fee_x"), unordered.toBuffer()),
+          feeVaultY: pda(Buffer.from("fee_y"), unordered.toBuffer()),
+          oracle: pda(Buffer.from("oracle"), unordered.toBuffer()),
+          registry,
+          pair: pda(
+            Buffer.from("pair"),
+            mintY.publicKey.toBuffer(),
+            mintX.publicKey.toBuffer()
+          ),
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([admin, ...vaults])
+        .rpc();
+      assert.fail("initialized a pool with unordered mints");
+    } catch (e) {
+      assert.equal(e.error.errorCode.code, "InvalidMintOrder");
+    }
+  });
+
+  it("Deposit", async () => {
+    await program.methods
+      .deposit(
+        new anchor.BN(100e6 - minimumLiquidity),
+        new anchor.BN(100e6),
+        new anchor.BN(100e6),
+        expiration(),
+        null
+      )
+      .accounts({ ...poolAccounts(), userLpAta: userAtaLp, lpLock, auth })
+      .signers([user])
+      .rpc()
+      .then(confirm)
+      .then(log);
+
+    assert.equal(await balance(vaultX.publicKey), BigInt(100e6));
+    assert.equal(await balance(vaultY.publicKey), BigInt(100e6));
+    assert.equal(
+      await balance(userAtaLp),
+      BigInt(100e6 - minimumLiquidity)
+    );
+    assert.equal(await balance(lpLock), BigInt(minimumLiquidity));
+  });
+
+  it("Swap X for Y", async () => {
+    const before = await balance(userAtaY);
+
+    await program.methods
+      .swap(new anchor.BN(10e6), new anchor.BN(1), expiration(), null, true)
+      .accounts(poolAccounts())
+      .signers([user])
+      .rpc()
+      .then(confirm)
+      .then(log);
+
+    const protocolCut = await balance(feeVaultX);
+    assert.isTrue(protocolCut > BigInt(0));
+    assert.equal(
+      (await balance(vaultX.publicKey)) + protocolCut,
+      BigInt(110e6)
+    );
+    assert.isTrue((await balance(userAtaY)) > before);
+  });
+
+  it("Swap Y for X", async () => {
+    const before = await balance(userAtaX);
+
+    await program.methods
+      .swap(new anchor.BN(5e6), new anchor.BN(1), expiration(), null, false)
+      .accounts(poolAccounts())
+      .signers([user])
+      .rpc()
+      .then(confirm)
+      .then(log);
+
+    assert.isTrue((await balance(userAtaX)) > before);
+  });
+
+  it("Swap exact out", async () => {
+    const before = await balance(userAtaY);
+
+    await program.methods
+      .swapExactOut(
+        new anchor.BN(1e6),
+        new anchor.BN(2e6),
+        expiration(),
+        null,
+        true
+      )
+      .accounts(poolAccounts())
+      .signers([user])
+      .rpc()
+      .then(confirm)
+      .then(log);
+
+    assert.equal((await balance(userAtaY)) - before, BigInt(1e6));
+  });
+
+  it("Rejects swaps past their slot deadline or trade cap", async () => {
+    const slot = await connection.getSlot();
+
+    try {
+      await program.methods
+        .swap(
+          new anchor.BN(1e6),
+          new anchor.BN(1),
+          expiration(),
+          new anchor.BN(slot - 1),
+          true
+        )
+        .accounts(poolAccounts())
+        .signers([user])
+        .rpc();
+      assert.fail("swap should fail after its slot deadline");
+    } catch (e) {
+      assert.equal(e.error.errorCode.code, "SlotDeadlineExceeded");
+    }
+
+    // Cap trades at 1% of the reserves.
+    await program.methods
+      .updateMaxTrade(100)
+      .accounts({ authority: admin.publicKey, config })
+      .signers([admin])
+      .rpc()
+      .then(confirm);
+
+    try {
+      await program.methods
+        .swap(new anchor.BN(5e6), new anchor.BN(1), expiration(), null, true)
+        .accounts(poolAccounts())
+        .signers([user])
+        .rpc();
+      assert.fail("swap above the trade cap should fail");
+    } catch (e) {
+      assert.equal(e.error.errorCode.code, "TradeTooLarge");
+    }
+
+    await program.methods
+      .updateMaxTrade(0)
+      .accounts({ authority: admin.publicKey, config })
+      .signers([admin])
+      .rpc()
+      .then(confirm);
+  });
+
+  it("Flash loan", async () => {
+    const accounts = {
+      user: user.publicKey,
+      config,
+      mintX: mintX.publicKey,
+      mintY: mintY.publicKey,
+      userXAta: userAtaX,
+      userYAta: userAtaY,
+      vaultX: vaultX.publicKey,
+      vaultY: vaultY.publicKey,
+      instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    };
+    const before = await balance(vaultX.publicKey);
+
+    const tx = new Transaction().add(
+      await program.methods
+        .flashLoan(new anchor.BN(10e6), true)
+        .accounts(accounts)
+        .instruction(),
+      await program.methods.flashRepay().accounts(accounts).instruction()
+    );
+    await provider.sendAndConfirm(tx, [user]).then(log);
+
+    assert.equal(
+      (await balance(vaultX.publicKey)) - before,
+      BigInt((10e6 * flashLoanFee) / 10_000)
+    );
+
+    try {
+      await program.methods
+        .flashLoan(new anchor.BN(10e6), true)
+        .accounts(accounts)
+        .signers([user])
+        .rpc();
+      assert.fail("a flash loan without repayment should fail");
+    } catch (e) {
+      assert.equal(e.error.errorCode.code, "InvalidFlashLoan");
+    }
+  });
+
+  it("Oracle tracks swaps", async () => {
+    const state = await program.account.oracle.fetch(oracle);
+    assert.isTrue(state.lastUpdated.toNumber() > 0);
+    assert.isTrue(state.observations[state.observationIndex].timestamp.gtn(0));
+  });
+
+  it("Collect protocol fees", async () => {
+    const owed = await balance(feeVaultX);
+
+    await program.methods
+      .collectProtocolFees()
+      .accounts({
+        authority: admin.publicKey,
+        config,
+        mintX: mintX.publicKey,
+        mintY: mintY.publicKey,
+        feeVaultX,
+        feeVaultY,
+        treasuryX,
+        treasuryY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([admin])
+      .rpc()
+      .then(confirm)
+      .then(log);
+
+    assert.equal(await balance(feeVaultX), BigInt(0));
+    assert.equal(await balance(treasuryX), owed);
+  });
+
+  it("Lock blocks swaps", async () => {
+    await program.methods
+      .lock()
+      .accounts({ authority: admin.publicKey, config })
+      .signers([admin])
+      .rpc()
+      .then(confirm);
+
+    try {
+      await program.methods
+        .swap(new anchor.BN(1e6), new anchor.BN(1), expiration(), null, true)
+        .accounts(poolAccounts())
+        .signers([user])
+        .rpc();
+      assert.fail("swap should fail while the pool is locked");
+    } catch (e) {
+      assert.equal(e.error.errorCode.code, "Locked");
+    }
+
+    await program.methods
+      .unlock()
+      .accounts({ authority: admin.publicKey, config })
+      .signers([admin])
+      .rpc()
+      .then(confirm);
+  });
+
+  it("Farm streams rewards to stakers", async () => {
+    // Rewards are paid in mint Y, funded from the admin's treasury.
+    await provider
+      .sendAndConfirm(
+        new Transaction().add(
+          createMintToInstruction(
+            mintY.publicKey,
+            treasuryY,
+            user.publicKey,
+            36e6
+          )
+        ),
+        [user]
+      )
+      .then(log);
+
+    await program.methods
+      .initializeFarm()
+      .accounts({
+        authority: admin.publicKey,
+        config,
+        mintLp,
+        rewardMint: mintY.publicKey,
+        farm,
+        stakeVault,
+        rewardVault,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([admin])
+      .rpc()
+      .then(confirm)
+      .then(log);
+
+    await program.methods
+      .fundRewards(new anchor.BN(36e6), new anchor.BN(3600))
+      .accounts({
+        authority: admin.publicKey,
+        config,
+        farm,
+        rewardMint: mintY.publicKey,
+        authorityRewardAta: treasuryY,
+        rewardVault,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([admin])
+      .rpc()
+      .then(confirm)
+      .then(log);
+
+    let state = await program.account.farm.fetch(farm);
+    assert.equal(state.rewardRate.toNumber(), 10_000);
+
+    const staked = await balance(userAtaLp);
+    const stakeAccounts = {
+      owner: user.publicKey,
+      config,
+      farm,
+      mintLp,
+      stake,
+      userLpAta: userAtaLp,
+      stakeVault,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    };
+
+    await program.methods
+      .stake(new anchor.BN(staked.toString()))
+      .accounts(stakeAccounts)
+      .signers([user])
+      .rpc()
+      .then(confirm)
+      .then(log);
+
+    assert.equal(await balance(stakeVault), staked);
+    state = await program.account.farm.fetch(farm);
+    assert.equal(state.totalStaked.toString(), staked.toString());
+
+    await new Promise((resolve) => setTimeout(resolve, 2000));
+
+    const beforeY = await balance(userAtaY);
+    await program.methods
+      .claim()
+      .accounts({
+        owner: user.publicKey,
+        farm,
+        rewardMint: mintY.publicKey,
+        stake,
+        rewardVault,
+        userRewardAta: userAtaY,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc()
+      .then(confirm)
+      .then(log);
+
+    // The only staker earns the whole stream, less rounding dust.
+    assert.isTrue((await balance(userAtaY)) > beforeY);
+
+    await program.methods
+      .unstake(new anchor.BN(staked.toString()))
+      .accounts(stakeAccounts)
+      .signers([user])
+      .rpc()
+      .then(confirm)
+      .then(log);
+
+    assert.equal(await balance(userAtaLp), staked);
+    assert.equal(await balance(stakeVault), BigInt(0));
+  });
+
+  it("Dynamic fee rises with volatility", async () => {
+    await program.methods
+      .setDynamicFee(true, 10, 500)
+      .accounts({ authority: admin.publicKey, config })
+      .signers([admin])
+      .rpc()
+      .then(confirm);
+
+    let state = await program.account.config.fetch(config);
+    assert.isTrue(state.dynamicFee);
+    assert.equal(state.volatility, 0);
+
+    await program.methods
+      .swap(new anchor.BN(5e6), new anchor.BN(1), expiration(), null, true)
+      .accounts(poolAccounts())
+      .signers([user])
+      .rpc()
+      .then(confirm)
+      .then(log);
+
+    // A trade of about 5% of the reserves moves their price by about 9%.
+    state = await program.account.config.fetch(config);
+    assert.isTrue(state.volatility > 500);
+
+    try {
+      await program.methods
+        .setDynamicFee(true, 500, 10)
+        .accounts({ authority: admin.publicKey, config })
+        .signers([admin])
+        .rpc();
+      assert.fail("min fee above max fee should be rejected");
+    } catch (e) {
+      assert.equal(e.error.errorCode.code, "InvalidFeeBounds");
+    }
+
+    await program.methods
+      .setDynamicFee(false, 0, 0)
+      .accounts({ authority: admin.publicKey, config })
+      .signers([admin])
+      .rpc()
+      .then(confirm);
+  });
+
+  it("Withdraw", async () => {
+    const beforeX = await balance(userAtaX);
+    const beforeY = await balance(userAtaY);
+
+    await program.methods
+      .withdraw(
+        new anchor.BN(100e6 - minimumLiquidity),
+        new anchor.BN(1),
+        new anchor.BN(1),
+        expiration(),
+        null
+      )
+      .accounts({ ...poolAccounts(), userLpAta: userAtaLp })
+      .signers([user])
+      .rpc()
+      .then(confirm)
+      .then(log);
+
+    assert.equal(await balance(userAtaLp), BigInt(0));
+    assert.isTrue((await balance(userAtaX)) > beforeX);
+    assert.isTrue((await balance(userAtaY)) > beforeY);
+  });
+
+  it("Update fee", async () => {
+    await program.methods
+      .updateFee(40, 10)
+      .accounts({ authority: admin.publicKey, config })
+      .signers([admin])
+      .rpc()
+      .then(confirm);
+
+    const state = await program.account.config.fetch(config);
+    assert.equal(state.fee, 40);
+    assert.equal(state.protocolFee, 10);
+  });
+
+  it("Initialize concentrated pool", async () => {
+    await program.methods
+      .initializeAmm(
+        clSeed,
+        fee,
+        protocolFee,
+        { concentrated: {} },
+        new anchor.BN(0),
+        flashLoanFee
+      )
+      .accounts({
+        initializer: admin.publicKey,
+        config: clConfig,
+        mintLp: pda(Buffer.from("lp"), clConfig.toBuffer()),
+        auth: pda(Buffer.from("auth"), clConfig.toBuffer()),
+        dead: pda(Buffer.from("dead"), clConfig.toBuffer()),
+        lpLock: pda(Buffer.from("lp_lock"), clConfig.toBuffer()),
+        mintX: mintX.publicKey,
+        mintY: mintY.publicKey,
+        vaultX: clVaultX.publicKey,
+        vaultY: clVaultY.publicKey,
+        feeVaultX: pda(Buffer.from("fee_x"), clConfig.toBuffer()),
+        feeVaultY: pda(Buffer.from("fee_y"), clConfig.toBuffer()),
+        oracle: pda(Buffer.from("oracle"), clConfig.toBuffer()),
+        registry,
+        pair,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([admin, clVaultX, clVaultY])
+      .rpc()
+      .then(confirm);
+
+    // Same fee tier as the first pool but a different curve.
+    const index = await program.account.pairIndex.fetch(pair);
+    assert.equal(index.pools.length, 2);
+    assert.ok(index.pools[1].config.equals(clConfig));
+    assert.isTrue(index.pools[1].canonical);
+
+    await program.methods
+      .initializeConcentrated(8, new anchor.BN(1).shln(64))
+      .accounts({
+        authority: admin.publicKey,
+        config: clConfig,
+        pool: clPool,
+        bitmap,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([admin])
+      .rpc()
+      .then(confirm)
+      .then(log);
+
+    const state = await program.account.concentratedPool.fetch(clPool);
+    assert.equal(state.tickCurrent, 0);
+  });
+
+  it("Open concentrated position", async () => {
+    await program.methods
+      .openPosition(tickLower, tickUpper)
+      .accounts({
+        owner: user.publicKey,
+        config: clConfig,
+        pool: clPool,
+        lowerTick,
+        upperTick,
+        position,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc()
+      .then(confirm);
+
+    await program.methods
+      .increaseLiquidity(
+        new anchor.BN(1e9),
+        new anchor.BN(100e6),
+        new anchor.BN(100e6),
+        expiration(),
+        null
+      )
+      .accounts(positionAccounts())
+      .signers([user])
+      .rpc()
+      .then(confirm)
+      .then(log);
+
+    const state = await program.account.concentratedPool.fetch(clPool);
+    assert.equal(state.liquidity.toString(), "1000000000");
+    assert.ok((await balance(clVaultX.publicKey)) > BigInt(0));
+    assert.ok((await balance(clVaultY.publicKey)) > BigInt(0));
+  });
+
+  it("Concentrated swap and collect fees", async () => {
+    const before = await balance(userAtaY);
+
+    await program.methods
+      .swapConcentrated(
+        new anchor.BN(1e6),
+        new anchor.BN(1),
+        expiration(),
+        null,
+        true
+      )
+      .accounts({
+        user: user.publicKey,
+        config: clConfig,
+        mintX: mintX.publicKey,
+        mintY: mintY.publicKey,
+        pool: clPool,
+        bitmap,
+        userXAta: userAtaX,
+        userYAta: userAtaY,
+        vaultX: clVaultX.publicKey,
+        vaultY: clVaultY.publicKey,
+        feeVaultX: pda(Buffer.from("fee_x"), clConfig.toBuffer()),
+        feeVaultY: pda(Buffer.from("fee_y"), clConfig.toBuffer()),
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([user])
+      .rpc()
+      .then(confirm)
+      .then(log);
+
+    assert.ok((await balance(userAtaY)) > before);
+
+    await program.methods
+      .collectFees()
+      .accounts(positionAccounts())
+      .signers([user])
+      .rpc()
+      .then(confirm);
+
+    const state = await program.account.position.fetch(position);
+    assert.ok(state.feeGrowthInsideXLast.gtn(0));
+  });
+
+  it("Close concentrated position", async () => {
+    await program.methods
+      .decreaseLiquidity(
+        new anchor.BN(1e9),
+        new anchor.BN(0),
+        new anchor.BN(0),
+        expiration(),
+        null
+      )
+      .accounts(positionAccounts())
+      .signers([user])
+      .rpc()
+      .then(confirm);
+
+    await program.methods
+      .closePosition()
+      .accounts({ owner: user.publicKey, config: clConfig, position })
+      .signers([user])
+      .rpc()
+      .then(confirm);
+
+    assert.isNull(await connection.getAccountInfo(position));
+  });
+
+  it("Amplification ramp needs a StableSwap pool", async () => {
+    try {
+      await program.methods
+        .rampAmp(
+          new anchor.BN(100),
+          new anchor.BN(Math.floor(Date.now() / 1000) + 2 * 86_400)
+        )
+        .accounts({ authority: admin.publicKey, config })
+        .signers([admin])
+        .rpc();
+      assert.fail("ramp should fail on a constant-product pool");
+    } catch (e) {
+      assert.equal(e.error.errorCode.code, "InvalidCurve");
+    }
+  });
+
+  it("Transfer authority", async () => {
+    await program.methods
+      .proposeAuthority(newAuthority.publicKey)
+      .accounts({ authority: admin.publicKey, config })
+      .signers([admin])
+      .rpc()
+      .then(confirm);
+
+    await program.methods
+      .acceptAuthority()
+      .accounts({ pendingAuthority: newAuthority.publicKey, config })
+      .signers([newAuthority])
+      .rpc()
+      .then(confirm);
+
+    const state = await program.account.config.fetch(config);
+    assert.ok(state.authority.equals(newAuthority.publicKey));
+    assert.isNull(state.pendingAuthority);
+  });
+
+  it("Freeze pool", async () => {
+    const pools = (await program.account.registry.fetch(registry)).pools;
+
+    await program.methods
+      .freezePool()
+      .accounts({ authority: newAuthority.publicKey, config, pair, registry })
+      .signers([newAuthority])
+      .rpc()
+      .then(confirm);
+
+    const state = await program.account.config.fetch(config);
+    assert.isTrue(state.locked);
+    assert.isTrue(state.closing);
+    assert.equal(
+      (await program.account.registry.fetch(registry)).pools.toNumber(),
+      pools.toNumber() - 1
+    );
+
+    const index = await program.account.pairIndex.fetch(pair);
+    assert.isFalse(index.pools.some((p) => p.config.equals(config)));
+
+    try {
+      await program.methods
+        .unlock()
+        .accounts({ authority: newAuthority.publicKey, config })
+        .signers([newAuthority])
+        .rpc();
+      assert.fail("a frozen pool must not unlock");
+    } catch (e) {
+      assert.equal(e.error.errorCode.code, "PoolFrozen");
+    }
+  });
+
+  it("Close pool", async () => {
+    // All LP has been withdrawn, so only the locked minimum is left and no
+    // holders need to be paid out.
+    const rent = await connection.getBalance(newAuthority.publicKey);
+
+    await program.methods
+      .closePool()
+      .accounts({
+        authority: newAuthority.publicKey,
+        config,
+        mintLp,
+        mintX: mintX.publicKey,
+        mintY: mintY.publicKey,
+        dead,
+        lpLock,
+        vaultX: vaultX.publicKey,
+        vaultY: vaultY.publicKey,
+        feeVaultX,
+        feeVaultY,
+        treasuryX,
+        treasuryY,
+        oracle,
+        farm,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([newAuthority])
+      .rpc()
+      .then(confirm)
+      .then(log);
 
-  it("Is initialized!", async () => {
-    // Add your test here.
-    const tx = await program.methods.initialize().rpc();
-    console.log("Your transaction signature", tx);
+    for (const account of [
+      config,
+      oracle,
+      lpLock,
+      vaultX.publicKey,
+      feeVaultX,
+    ]) {
+      assert.isNull(await connection.getAccountInfo(account));
+    }
+    assert.isTrue((await connection.getBalance(newAuthority.publicKey)) > rent);
   });
 });
@@ -0,0 +1,96 @@
+//! Token-2022 helpers shared by the AMM and escrow programs: which mint
+//! extensions they accept, and the transfer fees a mint withholds.
+//!
+//! Everything works on the raw mint account so that each program can map
+//! failures onto its own error codes. Mints owned by the legacy SPL Token
+//! program have no extensions and charge no fees.
+
+use solana_program::{
+    account_info::AccountInfo, clock::Clock, program_error::ProgramError, sysvar::Sysvar,
+};
+use spl_token_2022::{
+    extension::{
+        transfer_fee::TransferFeeConfig, BaseStateWithExtensions, ExtensionType,
+        StateWithExtensions,
+    },
+    state::Mint,
+};
+
+/// Token-2022 mint extensions the programs can hold. Anything else, transfer
+/// hooks and non-transferable mints in particular, could block or redirect
+/// their transfers.
+pub const SUPPORTED_EXTENSIONS: [ExtensionType; 5] = [
+    ExtensionType::TransferFeeConfig,
+    ExtensionType::MintCloseAuthority,
+    ExtensionType::InterestBearingConfig,
+    ExtensionType::MetadataPointer,
+    ExtensionType::TokenMetadata,
+];
+
+/// Whether every extension of `mint` is in `SUPPORTED_EXTENSIONS`.
+pub fn is_supported_mint(mint: &AccountInfo) -> Result<bool, ProgramError> {
+    if *mint.owner != spl_token_2022::ID {
+        return Ok(true);
+    }
+
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<Mint>::unpack(&data)?;
+
+    Ok(state
+        .get_extension_types()?
+        .iter()
+        .all(|extension| SUPPORTED_EXTENSIONS.contains(extension)))
+}
+
+/// Whether `mint` charges transfer fees.
+pub fn has_transfer_fee(mint: &AccountInfo) -> Result<bool, ProgramError> {
+    if *mint.owner != spl_token_2022::ID {
+        return Ok(false);
+    }
+
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<Mint>::unpack(&data)?;
+
+    Ok(state.get_extension::<TransferFeeConfig>().is_ok())
+}
+
+/// Fee withheld by `mint` when `amount` is transferred in the current epoch.
+/// `None` if the fee does not fit in a `u64`.
+pub fn transfer_fee(mint: &AccountInfo, amount: u64) -> Result<Option<u64>, ProgramError> {
+    if *mint.owner != spl_token_2022::ID {
+        return Ok(Some(0));
+    }
+
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<Mint>::unpack(&data)?;
+
+    let fee = match state.get_extension::<TransferFeeConfig>() {
+        Ok(config) => config.calculate_epoch_fee(Clock::get()?.epoch, amount),
+        Err(_) => Some(0),
+    };
+
+    Ok(fee)
+}
+
+/// Amount received when `amount` of `mint` is transferred.
+pub fn net_amount(mint: &AccountInfo, amount: u64) -> Result<Option<u64>, ProgramError> {
+    Ok(transfer_fee(mint, amount)?.and_then(|fee| amount.checked_sub(fee)))
+}
+
+/// Amount of `mint` to transfer so that exactly `net` is received. `None` if
+/// it does not fit in a `u64`.
+pub fn gross_amount(mint: &AccountInfo, net: u64) -> Result<Option<u64>, ProgramError> {
+    if *mint.owner != spl_token_2022::ID || net == 0 {
+        return Ok(Some(net));
+    }
+
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<Mint>::unpack(&data)?;
+
+    let fee = match state.get_extension::<TransferFeeConfig>() {
+        Ok(config) => config.calculate_inverse_epoch_fee(Clock::get()?.epoch, net),
+        Err(_) => Some(0),
+    };
+
+    Ok(fee.and_then(|fee| net.checked_add(fee)))
+}
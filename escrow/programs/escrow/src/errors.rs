@@ -0,0 +1,9 @@
+use anchor_lang::prelude::*;
+
+#[error_code]
+pub enum EscrowError {
+    #[msg("The mint uses a Token-2022 extension escrows do not support, such as a transfer hook or non-transferability.")]
+    UnsupportedMint,
+    #[msg("Invalid amount.")]
+    InvalidAmount,
+}
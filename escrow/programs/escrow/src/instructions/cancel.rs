@@ -7,7 +7,7 @@ use anchor_spl::{
     },
 };
 
-use crate::state::Escrow;
+use crate::{state::Escrow, token::harvest_withheld_fees};
 
 #[derive(Accounts)]
 pub struct Cancel<'info> {
@@ -22,6 +22,8 @@ pub struct Cancel<'info> {
     )]
     pub escrow: Account<'info, Escrow>,
 
+    /// Receives the transfer fees withheld in the escrow account.
+    #[account(mut)]
     pub x_mint: InterfaceAccount<'info, Mint>,
 
     pub y_mint: InterfaceAccount<'info, Mint>,
@@ -82,6 +84,12 @@ impl<'info> Cancel<'info> {
     }
 
     fn close_accounts(&mut self) -> Result<()> {
+        harvest_withheld_fees(
+            &self.token_program,
+            &self.x_mint,
+            &self.escrow_x_token_account,
+        )?;
+
         let maker_key = self.maker.key();
         let seed = self.escrow.seed.to_le_bytes();
 
@@ -4,7 +4,10 @@ use anchor_spl::{
     token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
 };
 
-use crate::state::Escrow;
+use crate::{
+    state::Escrow,
+    token::{check_mint, net_amount},
+};
 
 #[derive(Accounts)]
 #[instruction(seed: u64)]
@@ -53,13 +56,15 @@ impl<'info> Make<'info> {
         seed: u64,
         x_amount_deposited: u64,
         y_amount_expected: u64,
+        bumps: &MakeBumps,
     ) -> Result<()> {
-        self.set_escrow(
-            seed,
-            x_amount_deposited,
-            y_amount_expected,
-            self.escrow.bump,
-        )?;
+        check_mint(&self.x_mint)?;
+        check_mint(&self.y_mint)?;
+
+        // Record what the escrow actually holds after the mint's transfer fee.
+        let x_amount_received = net_amount(&self.x_mint, x_amount_deposited)?;
+
+        self.set_escrow(seed, x_amount_received, y_amount_expected, bumps.escrow)?;
 
         self.transfer_tokens(x_amount_deposited)
     }
@@ -90,7 +95,7 @@ impl<'info> Make<'info> {
         let cpi_program = self.token_program.to_account_info();
         let cpi_accounts = TransferChecked {
             from: self.maker_x_token_account.to_account_info(),
-            to: self.escrow.to_account_info(),
+            to: self.escrow_x_token_account.to_account_info(),
             authority: self.maker.to_account_info(),
             mint: self.x_mint.to_account_info(),
         };
@@ -7,7 +7,10 @@ use anchor_spl::{
     },
 };
 
-use crate::state::Escrow;
+use crate::{
+    state::Escrow,
+    token::{gross_amount, harvest_withheld_fees},
+};
 
 #[derive(Accounts)]
 pub struct Take<'info> {
@@ -27,6 +30,8 @@ pub struct Take<'info> {
     )]
     pub escrow: Account<'info, Escrow>,
 
+    /// Receives the transfer fees withheld in the escrow account.
+    #[account(mut)]
     pub x_mint: InterfaceAccount<'info, Mint>,
 
     pub y_mint: InterfaceAccount<'info, Mint>,
@@ -76,6 +81,10 @@ impl<'info> Take<'info> {
     }
 
     fn transfer_from_taker_to_maker(&mut self) -> Result<()> {
+        // The taker covers the transfer fee so the maker receives the full
+        // expected amount.
+        let amount = gross_amount(&self.y_mint, self.escrow.y_amount_expected)?;
+
         let cpi_program = self.token_program.to_account_info();
         let cpi_accounts = TransferChecked {
             from: self.taker_y_token_account.to_account_info(),
@@ -85,7 +94,7 @@ impl<'info> Take<'info> {
         };
         let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
 
-        transfer_checked(cpi_ctx, self.escrow.y_amount_expected, self.y_mint.decimals)
+        transfer_checked(cpi_ctx, amount, self.y_mint.decimals)
     }
 
     fn tranfer_from_escrow_to_taker(&mut self) -> Result<()> {
@@ -117,6 +126,12 @@ impl<'info> Take<'info> {
     }
 
     fn close_accounts(&mut self) -> Result<()> {
+        harvest_withheld_fees(
+            &self.token_program,
+            &self.x_mint,
+            &self.escrow_x_token_account,
+        )?;
+
         let maker_key = self.maker.key();
         let seed = self.escrow.seed.to_le_bytes();
 
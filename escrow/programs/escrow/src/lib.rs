@@ -1,7 +1,9 @@
 use anchor_lang::prelude::*;
 
+mod errors;
 mod instructions;
 mod state;
+mod token;
 
 use instructions::*;
 
@@ -18,7 +20,7 @@ pub mod escrow {
         y_amount_expected: u64,
     ) -> Result<()> {
         ctx.accounts
-            .make(seed, x_amount_deposited, y_amount_expected)
+            .make(seed, x_amount_deposited, y_amount_expected, &ctx.bumps)
     }
 
     pub fn take(ctx: Context<Take>) -> Result<()> {
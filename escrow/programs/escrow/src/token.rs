@@ -0,0 +1,53 @@
+//! Program-side wrappers over `token-extensions`, failing with `EscrowError`.
+
+use anchor_lang::{prelude::*, solana_program::program::invoke};
+use anchor_spl::token_2022::spl_token_2022::extension::transfer_fee::instruction::harvest_withheld_tokens_to_mint;
+
+use crate::errors::EscrowError;
+
+/// Rejects Token-2022 mints with extensions the escrow does not support.
+pub fn check_mint<'info>(mint: &impl ToAccountInfo<'info>) -> Result<()> {
+    require!(
+        token_extensions::is_supported_mint(&mint.to_account_info())?,
+        EscrowError::UnsupportedMint
+    );
+
+    Ok(())
+}
+
+/// Amount received when `amount` is transferred.
+pub fn net_amount<'info>(mint: &impl ToAccountInfo<'info>, amount: u64) -> Result<u64> {
+    let net = token_extensions::net_amount(&mint.to_account_info(), amount)?
+        .ok_or(EscrowError::InvalidAmount)?;
+
+    Ok(net)
+}
+
+/// Amount to transfer so that exactly `net` is received.
+pub fn gross_amount<'info>(mint: &impl ToAccountInfo<'info>, net: u64) -> Result<u64> {
+    let gross = token_extensions::gross_amount(&mint.to_account_info(), net)?
+        .ok_or(EscrowError::InvalidAmount)?;
+
+    Ok(gross)
+}
+
+/// Moves the transfer fees withheld in `account` to its mint. Token-2022
+/// refuses to close an account that still holds withheld fees.
+pub fn harvest_withheld_fees<'info>(
+    token_program: &impl ToAccountInfo<'info>,
+    mint: &impl ToAccountInfo<'info>,
+    account: &impl ToAccountInfo<'info>,
+) -> Result<()> {
+    let mint = mint.to_account_info();
+    if !token_extensions::has_transfer_fee(&mint)? {
+        return Ok(());
+    }
+
+    let token_program = token_program.to_account_info();
+    let account = account.to_account_info();
+    let ix = harvest_withheld_tokens_to_mint(token_program.key, mint.key, &[account.key])?;
+
+    invoke(&ix, &[mint, account, token_program])?;
+
+    Ok(())
+}
@@ -1,16 +1,280 @@
 import * as anchor from "@coral-xyz/anchor";
-import { Program } from "@coral-xyz/anchor";
+
+import {
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  ExtensionType,
+  TOKEN_2022_PROGRAM_ID,
+  createAssociatedTokenAccountIdempotentInstruction,
+  createInitializeMint2Instruction,
+  createInitializeTransferFeeConfigInstruction,
+  createMintToInstruction,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getMint,
+  getMintLen,
+  getTransferFeeConfig,
+} from "@solana/spl-token";
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  Transaction,
+} from "@solana/web3.js";
+
 import { Escrow } from "../target/types/escrow";
+import { Program } from "@coral-xyz/anchor";
+import { assert } from "chai";
+import { randomBytes } from "crypto";
 
 describe("escrow", () => {
   // Configure the client to use the local cluster.
   anchor.setProvider(anchor.AnchorProvider.env());
-
+  const provider = anchor.getProvider();
   const program = anchor.workspace.Escrow as Program<Escrow>;
+  const connection = program.provider.connection;
+
+  const maker = Keypair.generate();
+  const taker = Keypair.generate();
+  const mintX = Keypair.generate();
+  const mintY = Keypair.generate();
+
+  // Both mints withhold 1% of every transfer.
+  const transferFeeBps = 100;
+  const transferFee = (amount: number) =>
+    Math.ceil((amount * transferFeeBps) / 10_000);
+
+  const deposit = 1_000_000;
+  const expected = 2_000_000;
+
+  const ata = (mint: Keypair, owner: PublicKey) =>
+    getAssociatedTokenAddressSync(
+      mint.publicKey,
+      owner,
+      true,
+      TOKEN_2022_PROGRAM_ID
+    );
+  const makerAtaX = ata(mintX, maker.publicKey);
+  const makerAtaY = ata(mintY, maker.publicKey);
+  const takerAtaX = ata(mintX, taker.publicKey);
+  const takerAtaY = ata(mintY, taker.publicKey);
+
+  const escrowAccounts = (seed: anchor.BN) => {
+    const escrow = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("escrow"),
+        maker.publicKey.toBuffer(),
+        seed.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
+
+    return {
+      escrow,
+      xMint: mintX.publicKey,
+      yMint: mintY.publicKey,
+      escrowXTokenAccount: ata(mintX, escrow),
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      tokenProgram: TOKEN_2022_PROGRAM_ID,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    };
+  };
+
+  const confirm = async (signature: string): Promise<string> => {
+    const block = await connection.getLatestBlockhash();
+    await connection.confirmTransaction({
+      signature,
+      ...block,
+    });
+    return signature;
+  };
+
+  const balance = async (address: PublicKey): Promise<bigint> =>
+    (await getAccount(connection, address, undefined, TOKEN_2022_PROGRAM_ID))
+      .amount;
+
+  const withheld = async (mint: Keypair): Promise<bigint> =>
+    getTransferFeeConfig(
+      await getMint(
+        connection,
+        mint.publicKey,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      )
+    ).withheldAmount;
+
+  const make = async (seed: anchor.BN) => {
+    const accounts = escrowAccounts(seed);
+
+    await program.methods
+      .make(seed, new anchor.BN(deposit), new anchor.BN(expected))
+      .accounts({
+        maker: maker.publicKey,
+        makerXTokenAccount: makerAtaX,
+        ...accounts,
+      })
+      .signers([maker])
+      .rpc()
+      .then(confirm);
+
+    return accounts;
+  };
+
+  it("Airdrop", async () => {
+    await Promise.all([
+      await connection
+        .requestAirdrop(maker.publicKey, LAMPORTS_PER_SOL * 10)
+        .then(confirm),
+      await connection
+        .requestAirdrop(taker.publicKey, LAMPORTS_PER_SOL * 10)
+        .then(confirm),
+    ]);
+  });
+
+  it("Setup transfer-fee mints", async () => {
+    const space = getMintLen([ExtensionType.TransferFeeConfig]);
+    const lamports = await connection.getMinimumBalanceForRentExemption(space);
+
+    let tx = new Transaction();
+    for (const mint of [mintX, mintY]) {
+      tx.add(
+        anchor.web3.SystemProgram.createAccount({
+          fromPubkey: provider.publicKey,
+          newAccountPubkey: mint.publicKey,
+          lamports,
+          space,
+          programId: TOKEN_2022_PROGRAM_ID,
+        }),
+        createInitializeTransferFeeConfigInstruction(
+          mint.publicKey,
+          provider.publicKey,
+          provider.publicKey,
+          transferFeeBps,
+          BigInt(deposit * 10),
+          TOKEN_2022_PROGRAM_ID
+        ),
+        createInitializeMint2Instruction(
+          mint.publicKey,
+          6,
+          provider.publicKey,
+          null,
+          TOKEN_2022_PROGRAM_ID
+        )
+      );
+    }
+    for (const [address, owner, mint] of [
+      [makerAtaX, maker.publicKey, mintX],
+      [makerAtaY, maker.publicKey, mintY],
+      [takerAtaX, taker.publicKey, mintX],
+      [takerAtaY, taker.publicKey, mintY],
+    ] as [PublicKey, PublicKey, Keypair][]) {
+      tx.add(
+        createAssociatedTokenAccountIdempotentInstruction(
+          provider.publicKey,
+          address,
+          owner,
+          mint.publicKey,
+          TOKEN_2022_PROGRAM_ID
+        )
+      );
+    }
+    tx.add(
+      createMintToInstruction(
+        mintX.publicKey,
+        makerAtaX,
+        provider.publicKey,
+        deposit * 10,
+        [],
+        TOKEN_2022_PROGRAM_ID
+      ),
+      createMintToInstruction(
+        mintY.publicKey,
+        takerAtaY,
+        provider.publicKey,
+        expected * 10,
+        [],
+        TOKEN_2022_PROGRAM_ID
+      )
+    );
+
+    await provider.sendAndConfirm(tx, [mintX, mintY]);
+  });
+
+  it("Make and take", async () => {
+    const seed = new anchor.BN(randomBytes(8));
+    const accounts = await make(seed);
+
+    // The escrow records what it holds after the transfer fee.
+    const state = await program.account.escrow.fetch(accounts.escrow);
+    const received = deposit - transferFee(deposit);
+    assert.equal(state.xAmountDeposited.toNumber(), received);
+    assert.equal(
+      await balance(accounts.escrowXTokenAccount),
+      BigInt(received)
+    );
+
+    const makerY = await balance(makerAtaY);
+    const takerX = await balance(takerAtaX);
+    const takerY = await balance(takerAtaY);
+    const withheldX = await withheld(mintX);
+
+    await program.methods
+      .take()
+      .accounts({
+        taker: taker.publicKey,
+        maker: maker.publicKey,
+        makerYTokenAccount: makerAtaY,
+        takerXTokenAccount: takerAtaX,
+        takerYTokenAccount: takerAtaY,
+        ...accounts,
+      })
+      .signers([taker])
+      .rpc()
+      .then(confirm);
+
+    // The taker covers the fee on Y so the maker gets the full amount.
+    assert.equal(await balance(makerAtaY), makerY + BigInt(expected));
+    assert.isTrue(await balance(takerAtaY) < takerY - BigInt(expected));
+    assert.equal(
+      await balance(takerAtaX),
+      takerX + BigInt(received - transferFee(received))
+    );
+
+    // The fee withheld in the escrow account moved to the mint, so the
+    // account could be closed.
+    assert.equal(
+      await withheld(mintX),
+      withheldX + BigInt(transferFee(deposit))
+    );
+    assert.isNull(
+      await connection.getAccountInfo(accounts.escrowXTokenAccount)
+    );
+    assert.isNull(await connection.getAccountInfo(accounts.escrow));
+  });
+
+  it("Make and cancel", async () => {
+    const seed = new anchor.BN(randomBytes(8));
+    const accounts = await make(seed);
+    const received = deposit - transferFee(deposit);
+    const makerX = await balance(makerAtaX);
+
+    await program.methods
+      .cancel()
+      .accounts({
+        maker: maker.publicKey,
+        makerXTokenAccount: makerAtaX,
+        ...accounts,
+      })
+      .signers([maker])
+      .rpc()
+      .then(confirm);
 
-  it("Is initialized!", async () => {
-    // Add your test here.
-    const tx = await program.methods.initialize().rpc();
-    console.log("Your transaction signature", tx);
+    assert.equal(
+      await balance(makerAtaX),
+      makerX + BigInt(received - transferFee(received))
+    );
+    assert.isNull(
+      await connection.getAccountInfo(accounts.escrowXTokenAccount)
+    );
+    assert.isNull(await connection.getAccountInfo(accounts.escrow));
   });
 });